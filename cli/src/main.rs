@@ -6,8 +6,15 @@ use ga4gh_sdk::clients::tes::models::ListTasksParams;
 use ga4gh_sdk::clients::tes::models::TesListTasksResponse;
 use ga4gh_sdk::clients::tes::models::TesState;
 use ga4gh_sdk::clients::tes::models::TesTask;
+use ga4gh_sdk::clients::tes::models::TesView;
+use ga4gh_sdk::clients::tes::logs::LogStream;
+use ga4gh_sdk::clients::tes::template::TemplateVars;
+use ga4gh_sdk::clients::tes::wait::WaitOptions;
 use clap::{arg, Command};
+use futures::TryStreamExt;
+use std::io::Write;
 use std::path::Path;
+use std::time::Duration;
 use std::error::Error;
 use log::{debug, error};
 
@@ -28,8 +35,12 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 .arg_required_else_help(true)
                 .subcommand(
                     Command::new("create")
-                        .about("Create a task")
-                        .arg(arg!(<TASK_FILE> "The task file to create"))
+                        .about("Create tasks")
+                        .arg(arg!(<TASK_FILE> ... "The task files to create, or directories of .tes and .json task files"))
+                        .arg(arg!(-c --concurrency [CONCURRENCY] "The maximum number of tasks created at the same time (default: 8)"))
+                        .arg(arg!(--var [VAR] "Set a template variable, as KEY=VALUE (repeatable)").multiple_occurrences(true))
+                        .arg(arg!(--"vars-file" [VARS_FILE] "Read template variables from a JSON, YAML or .env file"))
+                        .arg(arg!(--"allow-undefined" "Leave placeholders of undefined variables as they are"))
                         // .arg(arg!(--url <URL> "The URL for the task"))
                         .arg_required_else_help(true),
                 )
@@ -38,11 +49,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         .about("list all tasks")
                         .arg(arg!(-n --name_prefix [NAME_PREFIX] "The name prefix to filter tasks"))
                         .arg(arg!(-s --state [STATE] "The state to filter tasks"))
-                        .arg(arg!(-k --tag_key [TAG_KEY] "The tag key to filter tasks"))
-                        .arg(arg!(-v --tag_value [TAG_VALUE] "The tag value to filter tasks"))
+                        .arg(arg!(--tag [TAG] "Filter tasks by tag, as key=value or key to match any value (repeatable)").multiple_occurrences(true))
                         .arg(arg!(-p --page_size [PAGE_SIZE] "The page size for pagination"))
                         .arg(arg!(-t --page_token [PAGE_TOKEN] "The page token for pagination"))
                         .arg(arg!(-w --view [VIEW] "The view for the tasks"))
+                        .arg(arg!(-a --all "List the tasks of all pages, starting from --page_token"))
                 )
                 .subcommand(
                     Command::new("get")
@@ -57,10 +68,22 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         .arg(arg!(<id> "The id of the task which should be returned"))
                         .arg_required_else_help(true),
                 )
+                .subcommand(
+                    Command::new("logs")
+                        .about("print the logs of the task executors")
+                        .arg(arg!(<id> "The id of the task whose logs should be printed"))
+                        .arg(arg!(-e --executor [EXECUTOR] "Only print the logs of the executor with this index"))
+                        .arg(arg!(-f --follow "Keep printing new log text until the task finishes"))
+                        .arg(arg!(--stderr "Print stderr instead of stdout"))
+                        .arg_required_else_help(true),
+                )
                 .subcommand(
                     Command::new("cancel")
-                        .about("cancel the task")
-                        .arg(arg!(<id> "The id of the task which should be cancel"))
+                        .about("cancel the task, or every unfinished task matching the filters")
+                        .arg(arg!([id] "The id of the task which should be cancel"))
+                        .arg(arg!(-n --name_prefix [NAME_PREFIX] "Cancel the tasks with this name prefix"))
+                        .arg(arg!(-s --state [STATE] "Cancel the tasks in this state"))
+                        .arg(arg!(--tag [TAG] "Cancel the tasks with this tag, as key=value or key (repeatable)").multiple_occurrences(true))
                         .arg_required_else_help(true),
                 ),
         );
@@ -72,58 +95,105 @@ async fn main() -> Result<(), Box<dyn Error>> {
             let config = Configuration::from_file(ServiceType::TES)?;
 
             if let Some(("create", sub)) = sub.subcommand() {
-                let task_file = sub.value_of("TASK_FILE")
-                    .ok_or_else(|| anyhow::anyhow!("TASK_FILE argument is required"))?;
-                let path = Path::new(task_file);
-                if !path.exists() {
-                    error!("File does not exist: {:?}", path);
-                    std::process::exit(1);
+                let mut task_files = Vec::new();
+                for task_file in sub.values_of("TASK_FILE").into_iter().flatten() {
+                    let path = Path::new(task_file);
+                    if !path.exists() {
+                        error!("File does not exist: {:?}", path);
+                        std::process::exit(1);
+                    }
+                    if path.is_dir() {
+                        task_files.extend(task_files_in(path)?);
+                    } else {
+                        task_files.push(path.to_path_buf());
+                    }
+                }
+                let concurrency = sub.value_of("concurrency").map(|s| s.parse()).transpose()?.unwrap_or(8);
+
+                let mut vars = TemplateVars::new().with_strict(!sub.is_present("allow-undefined"));
+                if let Some(vars_file) = sub.value_of("vars-file") {
+                    vars = vars.with_file(Path::new(vars_file))?;
+                }
+                for assignment in sub.values_of("var").into_iter().flatten() {
+                    vars = vars.with_assignment(assignment)?;
+                }
+
+                let mut tasks = Vec::new();
+                for path in &task_files {
+                    let task_json = std::fs::read_to_string(path)
+                        .map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
+                    let testask: TesTask = serde_json::from_str(&task_json)
+                        .map_err(|e| format!("Failed to parse JSON in {}: {}", path.display(), e))?;
+                    tasks.push(vars.apply(&testask)?);
                 }
-                let task_json = match std::fs::read_to_string(path) {
-                    Ok(contents) => contents,
-                    Err(e) => {
-                        error!("Failed to read file: {}", e);
-                        task_file.to_string()
-                    },
-                };
-                let testask: TesTask = serde_json::from_str(&task_json)
-                    .map_err(|e| format!("Failed to parse JSON: {}", e))?;
 
                 match TES::new(&config).await {
                     Ok(tes) => {
-                        let task = tes.create(testask).await;
-                        println!("{:?}", task);
+                        if let [testask] = tasks.as_slice() {
+                            let task = tes.create_checked(testask.clone()).await.map(|(task, warnings)| {
+                                for warning in warnings {
+                                    eprintln!("warning: {}", warning);
+                                }
+                                task
+                            });
+                            println!("{:?}", task);
+                        } else {
+                            let results = tes.create_many(tasks, concurrency).await;
+                            let mut failed = 0;
+                            for (path, result) in task_files.iter().zip(results) {
+                                match result {
+                                    Ok(task) => println!("{}\t{}", path.display(), task.id),
+                                    Err(e) => {
+                                        failed += 1;
+                                        println!("{}\tERROR: {}", path.display(), e);
+                                    }
+                                }
+                            }
+                            if failed > 0 {
+                                return Err(format!("{} of {} tasks could not be created", failed, task_files.len()).into());
+                            }
+                        }
                     },
                     Err(e) => {
                         error!("Error creating TES instance: {:?}", e);
-                        return Err(e);
+                        return Err(e.into());
                     }
                 };
             }
 
             if let Some(("list", sub)) = sub.subcommand() {
                 let name_prefix = sub.value_of("name_prefix").map(|s| s.to_string());
-                let state = sub.value_of("state").map(|s| serde_json::from_str(s).expect("Invalid state"));
-                let _tag_key = sub.value_of("tag_key").map(|s| s.to_string());
-                let _tag_value = sub.value_of("tag_value").map(|s| s.to_string());
+                let state = sub.value_of("state").map(parse_state).transpose()?;
                 let page_size = sub.value_of("page_size").map(|s| s.parse().expect("Invalid page_size"));
                 let page_token = sub.value_of("page_token").map(|s| s.to_string());
-                let view = sub.value_of("view").map(|s| s.to_string());
+                let view = sub.value_of("view").map(|s| s.parse::<TesView>()).transpose()?;
 
-                let parameters = ListTasksParams {
+                let mut parameters = ListTasksParams {
                     name_prefix,
                     state,
-                    tag_key: None, // Example does not cover parsing Vec<String>
-                    tag_value: None, // Example does not cover parsing Vec<String>
+                    tag_key: None,
+                    tag_value: None,
                     page_size,
                     page_token,
                     view,
                 };
+                for tag in sub.values_of("tag").into_iter().flatten() {
+                    let (key, value) = tag.split_once('=').unwrap_or((tag, ""));
+                    parameters = parameters.with_tag(key.to_string(), value.to_string());
+                }
                 debug!("parameters are: {:?}", parameters);
 
                 match TES::new(&config).await {
                     Ok(tes) => {
-                        match tes.list_tasks(Some(parameters)).await {
+                        let response = if sub.is_present("all") {
+                            tes.list_all_tasks(Some(parameters), None)
+                                .try_collect()
+                                .await
+                                .map(TesListTasksResponse::new)
+                        } else {
+                            tes.list_tasks(Some(parameters)).await
+                        };
+                        match response {
                             Ok(task_response) => {
                                 println!("{}", format_tasks_response(&task_response)); 
                             },
@@ -134,14 +204,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     },
                     Err(e) => {
                         error!("Error creating TES instance: {:?}", e);
-                        return Err(e);
+                        return Err(e.into());
                     }
                 };
             }
 
             if let Some(("get", sub)) = sub.subcommand() {    
                 let id = sub.value_of("id").unwrap();
-                let view = sub.value_of("view").unwrap();
+                let view: TesView = sub.value_of("view").unwrap().parse()?;
 
                 match TES::new(&config).await {
                     Ok(tes) => {
@@ -150,14 +220,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     },
                     Err(e) => {
                         error!("Error creating TES instance: {:?}", e);
-                        return Err(e);
+                        return Err(e.into());
                     }
                 };
             }
 
             if let Some(("status", sub)) = sub.subcommand() {   
                 let id = sub.value_of("id").unwrap().to_string();
-                let transport = Transport::new(&config);
+                let transport = Transport::new(&config)?;
                 let task = Task::new(id.clone(), transport);
                 match task.status().await {
                     Ok(status) => {
@@ -166,14 +236,79 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     },
                     Err(e) => {
                         error!("Error creating Task instance: {:?}", e);
-                        return Err(e);
+                        return Err(e.into());
                     }
                 };
             }
 
-            if let Some(("cancel", sub)) = sub.subcommand() {   
+            if let Some(("logs", sub)) = sub.subcommand() {
                 let id = sub.value_of("id").unwrap().to_string();
-                let transport = Transport::new(&config);
+                let executor: Option<usize> = sub.value_of("executor").map(|s| s.parse()).transpose()?;
+                let stream = if sub.is_present("stderr") { LogStream::Stderr } else { LogStream::Stdout };
+                let transport = Transport::new(&config)?;
+                let task = Task::new(id, transport);
+
+                if sub.is_present("follow") {
+                    // follow_logs moves on to the new attempt when the task is retried
+                    let options = WaitOptions::default().with_backoff(1.5, Duration::from_secs(5));
+                    let chunks = task.follow_logs(options);
+                    futures::pin_mut!(chunks);
+                    while let Some(chunk) = chunks.try_next().await? {
+                        if chunk.stream != stream || executor.is_some_and(|executor| executor != chunk.executor) {
+                            continue;
+                        }
+                        print!("{}", chunk.text);
+                        std::io::stdout().flush()?;
+                    }
+                } else {
+                    // only the last attempt, as earlier ones were retried
+                    let logs = task.logs().await?;
+                    let executor_logs = logs.last().map(|log| log.logs.as_slice()).unwrap_or_default();
+                    for (i, log) in executor_logs.iter().enumerate() {
+                        if executor.is_some_and(|executor| executor != i) {
+                            continue;
+                        }
+                        let text = match stream {
+                            LogStream::Stdout => &log.stdout,
+                            LogStream::Stderr => &log.stderr,
+                        };
+                        print!("{}", text.as_deref().unwrap_or_default());
+                    }
+                }
+            }
+
+            if let Some(("cancel", sub)) = sub.subcommand() {
+                let Some(id) = sub.value_of("id").map(|s| s.to_string()) else {
+                    let mut filter = ListTasksParams {
+                        name_prefix: sub.value_of("name_prefix").map(|s| s.to_string()),
+                        state: sub.value_of("state").map(parse_state).transpose()?,
+                        ..Default::default()
+                    };
+                    for tag in sub.values_of("tag").into_iter().flatten() {
+                        let (key, value) = tag.split_once('=').unwrap_or((tag, ""));
+                        filter = filter.with_tag(key.to_string(), value.to_string());
+                    }
+                    if filter.name_prefix.is_none() && filter.state.is_none() && filter.tag_key.is_none() {
+                        return Err("Give a task id, or --name_prefix, --state or --tag to cancel matching tasks".into());
+                    }
+
+                    let tes = TES::new(&config).await?;
+                    let mut failed = 0;
+                    for (id, result) in tes.cancel_where(filter).await? {
+                        match result {
+                            Ok(_) => println!("{}\tCANCELED", id),
+                            Err(e) => {
+                                failed += 1;
+                                println!("{}\tERROR: {}", id, e);
+                            }
+                        }
+                    }
+                    if failed > 0 {
+                        return Err(format!("{} tasks could not be cancelled", failed).into());
+                    }
+                    return Ok(());
+                };
+                let transport = Transport::new(&config)?;
                 let task = Task::new(id, transport);
                 match task.cancel().await {
                     Ok(output) => {
@@ -181,7 +316,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     }
                     Err(e) => {
                         error!("Error creating Task instance: {:?}", e);
-                        return Err(e);
+                        return Err(e.into());
                     }
                 };
             }
@@ -197,6 +332,26 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
 // Helper functions
 
+fn parse_state(state: &str) -> Result<TesState, Box<dyn Error>> {
+    let state = state.trim_matches('"').to_uppercase();
+    serde_json::from_value(serde_json::Value::String(state.clone()))
+        .map_err(|_| format!("Invalid state: {}", state).into())
+}
+
+/// Returns the `.tes` and `.json` files of a directory, sorted by name.
+fn task_files_in(dir: &Path) -> Result<Vec<std::path::PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_task = path.extension().is_some_and(|extension| extension == "tes" || extension == "json");
+        if path.is_file() && is_task {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
 fn tes_state_to_str(state: &Option<TesState>) -> &str {
     match state {
         Some(TesState::Unknown) => "Unknown",
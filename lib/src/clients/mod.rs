@@ -1,3 +1,4 @@
+pub mod routes;
 pub mod serviceinfo;
 pub mod tes;
 pub mod trs;
@@ -22,4 +23,15 @@ impl ServiceType {
             ServiceType::WES => "WES",
         }
     }
+
+    /// Returns the path of the service's API in its specification, e.g. `/ga4gh/tes/v1`.
+    pub fn spec_path(&self) -> &str {
+        match self {
+            ServiceType::TES => "/ga4gh/tes/v1",
+            ServiceType::DRS => "/ga4gh/drs/v1",
+            ServiceType::TRS => "/ga4gh/trs/v2",
+            ServiceType::AAI => "",
+            ServiceType::WES => "/ga4gh/wes/v1",
+        }
+    }
 }
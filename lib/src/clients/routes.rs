@@ -0,0 +1,118 @@
+use crate::clients::ServiceType;
+use crate::utils::configuration::Configuration;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use url::Url;
+
+/// The characters percent-encoded in a path segment: everything but the unreserved ones.
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// Percent-encodes a value, such as a task ID, for use as a single path segment.
+///
+/// Slashes, colons, spaces and other reserved characters are encoded, so the value can never
+/// change the route it is placed in.
+pub fn encode_segment(value: &str) -> String {
+    utf8_percent_encode(value, SEGMENT).to_string()
+}
+
+/// Builds the URLs of a GA4GH API.
+///
+/// Every URL is made of the configured base URL (including its path), the optional
+/// deployment prefix and the path of the API in its specification, e.g. `/ga4gh/tes/v1`:
+/// with a base URL of `https://example.org/compute` and a prefix of `/prod`, the TES tasks
+/// live at `https://example.org/compute/prod/ga4gh/tes/v1/tasks`.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::clients::routes::{encode_segment, Routes};
+/// use ga4gh_sdk::clients::ServiceType;
+/// use ga4gh_sdk::utils::configuration::Configuration;
+///
+/// let config = Configuration::new(url::Url::parse("https://example.org/compute/").unwrap())
+///     .with_path_prefix("/prod".to_string());
+/// let routes = Routes::for_service(&config, ServiceType::TES);
+/// let url = routes.url(&format!("tasks/{}", encode_segment("task 1")));
+/// assert_eq!(url.as_str(), "https://example.org/compute/prod/ga4gh/tes/v1/tasks/task%201");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Routes {
+    root: Url,
+}
+
+impl Routes {
+    /// Creates the routes of the API at `spec_path` below `base_url` and `deployment_prefix`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - The base URL of the service.
+    /// * `deployment_prefix` - An extra path in front of the specification path, if any.
+    /// * `spec_path` - The path of the API in its specification, e.g. `/ga4gh/tes/v1`.
+    pub fn new(base_url: &Url, deployment_prefix: Option<&str>, spec_path: &str) -> Self {
+        let mut root = base_url.clone();
+        root.set_query(None);
+        root.set_fragment(None);
+        let mut path = root.path().trim_end_matches('/').to_string();
+        for part in [deployment_prefix.unwrap_or_default(), spec_path] {
+            let part = part.trim_matches('/');
+            if !part.is_empty() {
+                path.push('/');
+                path.push_str(part);
+            }
+        }
+        root.set_path(&path);
+        Routes { root }
+    }
+
+    /// Creates the routes of the given service from the base URL and deployment prefix of
+    /// the configuration.
+    pub fn for_service(config: &Configuration, service: ServiceType) -> Self {
+        Routes::new(&config.base_path, config.path_prefix.as_deref(), service.spec_path())
+    }
+
+    /// Returns the URL the API's paths are relative to, without a trailing slash.
+    pub fn root(&self) -> &Url {
+        &self.root
+    }
+
+    /// Returns the URL of a path of the API.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path below the API root, e.g. `tasks`. Values placed in it must be
+    ///   encoded with `encode_segment`.
+    pub fn url(&self, path: &str) -> Url {
+        let mut url = self.root.clone();
+        let path = path.trim_start_matches('/');
+        if !path.is_empty() {
+            url.set_path(&format!("{}/{}", self.root.path().trim_end_matches('/'), path));
+        }
+        url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes_keep_base_path() {
+        let base = Url::parse("https://example.org/compute").unwrap();
+        assert_eq!(
+            Routes::new(&base, None, "/ga4gh/tes/v1").url("tasks").as_str(),
+            "https://example.org/compute/ga4gh/tes/v1/tasks"
+        );
+        assert_eq!(
+            Routes::new(&base, Some("prod/"), "/ga4gh/tes/v1").url("/service-info").as_str(),
+            "https://example.org/compute/prod/ga4gh/tes/v1/service-info"
+        );
+
+        let root = Url::parse("http://localhost:8000").unwrap();
+        assert_eq!(Routes::new(&root, None, "").url("service-info").as_str(), "http://localhost:8000/service-info");
+    }
+
+    #[test]
+    fn test_encode_segment() {
+        assert_eq!(encode_segment("abc-123_x.y~z"), "abc-123_x.y~z");
+        assert_eq!(encode_segment("a/b:c d?e#f%"), "a%2Fb%3Ac%20d%3Fe%23f%25");
+    }
+}
@@ -1,11 +1,27 @@
 /// Represents a service information client.
 pub mod models;
+use crate::clients::routes::Routes;
 use crate::utils::configuration::Configuration;
+use crate::utils::telemetry::{operation_span, traced};
 use crate::utils::transport::Transport;
+use crate::error::from_json;
+use crate::Result;
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+/// The time after which fetching the service information fails, unless the configuration
+/// sets a request timeout, so creating a client does not hang on an unresponsive service.
+const SERVICE_INFO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The service information is fetched once and kept for the lifetime of the client, including
+/// its clones; use `refresh` to fetch it again.
 #[derive(Clone)]
 pub struct ServiceInfo {
     transport: Transport,
+    routes: Routes,
+    /// The body of the last successful response.
+    cached: Arc<Mutex<Option<String>>>,
 }
 
 impl ServiceInfo {
@@ -18,43 +34,103 @@ impl ServiceInfo {
     /// # Returns
     ///
     /// A `Result` containing the `ServiceInfo` instance or an error.
-    pub fn new(config: &Configuration) -> Result<Self, Box<dyn std::error::Error>> {
-        let transport = Transport::new(config);
-        let instance = ServiceInfo {
-            transport: transport.clone(),
-        };
-        Ok(instance)
+    pub fn new(config: &Configuration) -> Result<Self> {
+        let transport = Transport::new(config)?;
+        Ok(ServiceInfo::from_transport(transport))
+    }
+
+    /// Creates a new instance of `ServiceInfo` sending requests through the given transport.
+    ///
+    /// Use this to run the client against a custom `HttpBackend`.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport for sending HTTP requests.
+    ///
+    /// # Returns
+    ///
+    /// The `ServiceInfo` instance.
+    pub fn from_transport(transport: Transport) -> Self {
+        let config = &transport.config;
+        let routes = Routes::new(&config.base_path, config.path_prefix.as_deref(), "");
+        ServiceInfo {
+            transport,
+            routes,
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Fetches the service information below the given API root instead, e.g. at
+    /// `/ga4gh/tes/v1/service-info` for a TES.
+    ///
+    /// # Arguments
+    ///
+    /// * `routes` - The routes of the API the service information belongs to.
+    ///
+    /// # Returns
+    ///
+    /// The `ServiceInfo` instance.
+    pub fn with_routes(mut self, routes: Routes) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    /// Retrieves the service information, from the service on the first call and from memory
+    /// afterwards.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the service information or an error.
+    pub async fn get(&self) -> Result<models::Service> {
+        self.get_as().await
     }
 
-    /// Retrieves the service information.
+    /// Retrieves the service information as a service-specific model, e.g. `TesServiceInfo`,
+    /// from the same response as `get`.
     ///
     /// # Returns
     ///
     /// A `Result` containing the service information or an error.
-    pub async fn get(&self) -> Result<models::Service, Box<dyn std::error::Error>> {
-        let response = tokio::time::timeout(
-            std::time::Duration::from_secs(10),
-            self.transport.get("/service-info", None)
-        ).await?;
-        match response {
-            Ok(response_body) => match serde_json::from_str::<models::Service>(&response_body) {
-                Ok(service) => Ok(service),
-                Err(e) => {
-                    log::error!("Failed to deserialize response. Error: {}", e);
-                    Err(e.into())
-                }
-            },
-            Err(e) => {
-                log::error!("Error getting response: {}", e);
-                Err(e)
+    pub async fn get_as<T: DeserializeOwned>(&self) -> Result<T> {
+        let cached = self.cached.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        match cached {
+            Some(body) => from_json(&body),
+            None => {
+                let body = self.fetch().await?;
+                from_json(&body)
             }
         }
     }
+
+    /// Fetches the service information from the service and replaces the kept copy.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the service information or an error.
+    pub async fn refresh(&self) -> Result<models::Service> {
+        let body = self.fetch().await?;
+        from_json(&body)
+    }
+
+    /// Fetches the service information and keeps it if it is a valid `Service`.
+    async fn fetch(&self) -> Result<String> {
+        let body = traced(operation_span("service-info", "get_service_info"), async {
+            let url = self.routes.url("service-info");
+            let transport = self.transport.clone().with_default_timeout(SERVICE_INFO_TIMEOUT);
+            let response_body = transport.get(url.as_str(), None).await?;
+            from_json::<models::Service>(&response_body)?;
+            Ok(response_body)
+        })
+        .await?;
+        *self.cached.lock().unwrap_or_else(|e| e.into_inner()) = Some(body.clone());
+        Ok(body)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Error;
     use mockito::mock;
     use models::ServiceType;
     use url::Url;
@@ -94,6 +170,58 @@ mod tests {
         assert_eq!(service.version, "1.0.0");
     }
 
+    #[tokio::test]
+    async fn test_service_info_cached_for_client_lifetime() {
+        let service_type = ServiceType::new("org.ga4gh".to_string(), "tes".to_string(), "1.1.0".to_string());
+        let service_organization = models::ServiceOrganization::new("org_name".to_string(), "org_url".to_string());
+        let body = serde_json::json!({
+            "id": "123",
+            "name": "Cached Service",
+            "type": service_type,
+            "organization": service_organization,
+            "version": "1.0.0"
+        })
+        .to_string();
+        let m = mock("GET", "/service-info").with_status(200).with_body(&body).expect(2).create();
+
+        let mock_url = Url::parse(&mockito::server_url()).unwrap();
+        let service_info = ServiceInfo::new(&Configuration::new(mock_url)).unwrap();
+        let clone = service_info.clone();
+
+        assert_eq!(service_info.get().await.unwrap().name, "Cached Service");
+        assert_eq!(clone.get().await.unwrap().name, "Cached Service");
+        assert_eq!(service_info.refresh().await.unwrap().name, "Cached Service");
+        assert_eq!(clone.get().await.unwrap().name, "Cached Service");
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_service_info_default_timeout() {
+        use crate::utils::backend::{HttpBackend, HttpRequest, HttpResponse};
+        use crate::utils::timeouts::TimeoutConfig;
+
+        /// A backend reporting the timeout of each request as an error.
+        struct TimeoutEcho;
+
+        #[async_trait::async_trait]
+        impl HttpBackend for TimeoutEcho {
+            async fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+                Err(Error::InvalidInput(format!("{:?}", request.timeout)))
+            }
+        }
+
+        let config = Configuration::new(Url::parse("http://service.invalid").unwrap());
+        let service_info = ServiceInfo::from_transport(Transport::with_backend(&config, Arc::new(TimeoutEcho)).unwrap());
+        let error = service_info.get().await.unwrap_err();
+        assert!(matches!(error, Error::InvalidInput(timeout) if timeout == "Some(10s)"));
+
+        // a configured request timeout applies instead
+        let config = config.with_timeouts(TimeoutConfig::default().with_request(Duration::from_secs(60)));
+        let service_info = ServiceInfo::from_transport(Transport::with_backend(&config, Arc::new(TimeoutEcho)).unwrap());
+        let error = service_info.get().await.unwrap_err();
+        assert!(matches!(error, Error::InvalidInput(timeout) if timeout == "None"));
+    }
+
     #[tokio::test]
     async fn test_get_service_info_failure() {
         // Arrange
@@ -113,6 +241,9 @@ mod tests {
         let result = service_info.get().await;
 
         // Assert
-        assert!(result.is_err());
+        match result {
+            Err(Error::Http { status, .. }) => assert_eq!(status.as_u16(), 500),
+            other => panic!("unexpected result: {:?}", other),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,315 @@
+use crate::clients::tes::models::{TesExecutor, TesFileType, TesInput, TesOutput, TesResources, TesTask};
+use crate::clients::tes::staging::unstaged_violations;
+use crate::{Error, Result};
+use std::collections::HashMap;
+
+/// Builds a `TesTask` step by step and validates it.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::clients::tes::builder::{TesExecutorBuilder, TesTaskBuilder};
+/// use ga4gh_sdk::clients::tes::models::{TesInput, TesOutput};
+///
+/// let task = TesTaskBuilder::new()
+///     .name("word count".to_string())
+///     .input(TesInput::from_url("s3://bucket/input.txt".to_string(), "/data/input.txt".to_string()))
+///     .output(TesOutput::new("s3://bucket/count.txt".to_string(), "/data/count.txt".to_string()))
+///     .executor(
+///         TesExecutorBuilder::new("alpine".to_string())
+///             .command(vec!["wc".to_string(), "-w".to_string(), "/data/input.txt".to_string()])
+///             .stdout("/data/count.txt".to_string()),
+///     )
+///     .cpu_cores(1)
+///     .tag("project".to_string(), "demo".to_string())
+///     .build()
+///     .expect("the task is valid");
+/// assert_eq!(task.executors.unwrap()[0].image, "alpine");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TesTaskBuilder {
+    task: TesTask,
+}
+
+impl TesTaskBuilder {
+    /// Creates a builder for an empty task.
+    pub fn new() -> Self {
+        TesTaskBuilder::default()
+    }
+
+    /// Sets the name of the task.
+    pub fn name(mut self, name: String) -> Self {
+        self.task.name = Some(name);
+        self
+    }
+
+    /// Sets the description of the task.
+    pub fn description(mut self, description: String) -> Self {
+        self.task.description = Some(description);
+        self
+    }
+
+    /// Adds an input file or directory.
+    pub fn input(mut self, input: TesInput) -> Self {
+        self.task.inputs.get_or_insert_with(Vec::new).push(input);
+        self
+    }
+
+    /// Adds an output file or directory.
+    pub fn output(mut self, output: TesOutput) -> Self {
+        self.task.outputs.get_or_insert_with(Vec::new).push(output);
+        self
+    }
+
+    /// Adds an executor; executors run one after the other.
+    pub fn executor(mut self, executor: impl Into<TesExecutor>) -> Self {
+        self.task.executors.get_or_insert_with(Vec::new).push(executor.into());
+        self
+    }
+
+    /// Adds a volume shared between the executors.
+    pub fn volume(mut self, path: String) -> Self {
+        self.task.volumes.get_or_insert_with(Vec::new).push(path);
+        self
+    }
+
+    /// Adds a tag.
+    pub fn tag(mut self, key: String, value: String) -> Self {
+        self.task.tags.get_or_insert_with(HashMap::new).insert(key, value);
+        self
+    }
+
+    /// Sets the number of CPU cores requested.
+    pub fn cpu_cores(self, cpu_cores: i32) -> Self {
+        self.resources(|resources| resources.cpu_cores = Some(cpu_cores))
+    }
+
+    /// Sets the RAM requested, in gigabytes.
+    pub fn ram_gb(self, ram_gb: f64) -> Self {
+        self.resources(|resources| resources.ram_gb = Some(ram_gb))
+    }
+
+    /// Sets the disk space requested, in gigabytes.
+    pub fn disk_gb(self, disk_gb: f64) -> Self {
+        self.resources(|resources| resources.disk_gb = Some(disk_gb))
+    }
+
+    /// Sets whether the task may run on preemptible compute instances.
+    pub fn preemptible(self, preemptible: bool) -> Self {
+        self.resources(|resources| resources.preemptible = Some(preemptible))
+    }
+
+    /// Adds a zone the task may run in.
+    pub fn zone(self, zone: String) -> Self {
+        self.resources(|resources| resources.zones.get_or_insert_with(Vec::new).push(zone))
+    }
+
+    /// Adds a backend-specific parameter.
+    pub fn backend_parameter(self, key: String, value: String) -> Self {
+        self.resources(|resources| {
+            resources.backend_parameters.get_or_insert_with(HashMap::new).insert(key, value);
+        })
+    }
+
+    fn resources(mut self, update: impl FnOnce(&mut TesResources)) -> Self {
+        update(self.task.resources.get_or_insert_with(|| Box::new(TesResources::new())));
+        self
+    }
+
+    /// Validates the task and returns it.
+    ///
+    /// Inputs with a local URL, a bare path or a `file://` URL, are accepted, so the task can
+    /// be staged by `TES::create`; `create` rejects them if no `staging` is configured.
+    ///
+    /// # Returns
+    /// - The task, or `Error::Validation` with every violation of the specification.
+    pub fn build(self) -> Result<TesTask> {
+        if let Err(violations) = self.task.validate() {
+            let violations = unstaged_violations(&self.task, violations);
+            if !violations.is_empty() {
+                return Err(Error::Validation(violations));
+            }
+        }
+        Ok(self.task)
+    }
+}
+
+/// Builds a `TesExecutor`.
+#[derive(Debug, Clone)]
+pub struct TesExecutorBuilder {
+    executor: TesExecutor,
+}
+
+impl TesExecutorBuilder {
+    /// Creates a builder for an executor running the given container image.
+    pub fn new(image: String) -> Self {
+        TesExecutorBuilder {
+            executor: TesExecutor::new(image, Vec::new()),
+        }
+    }
+
+    /// Sets the command to run in the container.
+    pub fn command(mut self, command: Vec<String>) -> Self {
+        self.executor.command = command;
+        self
+    }
+
+    /// Sets the working directory of the command.
+    pub fn workdir(mut self, workdir: String) -> Self {
+        self.executor.workdir = Some(workdir);
+        self
+    }
+
+    /// Sets the path of the file the command reads its standard input from.
+    pub fn stdin(mut self, stdin: String) -> Self {
+        self.executor.stdin = Some(stdin);
+        self
+    }
+
+    /// Sets the path of the file the standard output of the command is written to.
+    pub fn stdout(mut self, stdout: String) -> Self {
+        self.executor.stdout = Some(stdout);
+        self
+    }
+
+    /// Sets the path of the file the standard error of the command is written to.
+    pub fn stderr(mut self, stderr: String) -> Self {
+        self.executor.stderr = Some(stderr);
+        self
+    }
+
+    /// Sets an environment variable.
+    pub fn env(mut self, key: String, value: String) -> Self {
+        self.executor.env.get_or_insert_with(HashMap::new).insert(key, value);
+        self
+    }
+
+    /// Sets whether the task goes on with the next executor when this one fails.
+    pub fn ignore_error(mut self, ignore_error: bool) -> Self {
+        self.executor.ignore_error = Some(ignore_error);
+        self
+    }
+
+    /// Returns the executor.
+    pub fn build(self) -> TesExecutor {
+        self.executor
+    }
+}
+
+impl From<TesExecutorBuilder> for TesExecutor {
+    fn from(builder: TesExecutorBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl TesInput {
+    /// Creates an input downloaded from a URL to a path in the container.
+    ///
+    /// The URL may also be a local path, uploaded by `TES::create` if `staging` is configured.
+    pub fn from_url(url: String, path: String) -> TesInput {
+        TesInput {
+            url: Some(url),
+            ..TesInput::new(path)
+        }
+    }
+
+    /// Creates an input file with the given content at a path in the container.
+    pub fn from_content(content: String, path: String) -> TesInput {
+        TesInput {
+            content: Some(content),
+            r#type: Some(TesFileType::File),
+            ..TesInput::new(path)
+        }
+    }
+
+    /// Creates an input directory downloaded from a URL to a path in the container.
+    pub fn directory(url: String, path: String) -> TesInput {
+        TesInput {
+            r#type: Some(TesFileType::Directory),
+            ..TesInput::from_url(url, path)
+        }
+    }
+}
+
+impl TesOutput {
+    /// Creates an output directory uploaded from a path in the container to a URL.
+    pub fn directory(url: String, path: String) -> TesOutput {
+        TesOutput {
+            r#type: Some(TesFileType::Directory),
+            ..TesOutput::new(url, path)
+        }
+    }
+
+    /// Creates an output of the files matching a wildcard pattern, e.g. `/data/*.txt`. Each
+    /// file is uploaded to `url` followed by its path without `path_prefix`.
+    pub fn matching(url: String, pattern: String, path_prefix: String) -> TesOutput {
+        TesOutput {
+            path_prefix: Some(path_prefix),
+            ..TesOutput::new(url, pattern)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_task() {
+        let task = TesTaskBuilder::new()
+            .input(TesInput::from_content("hello".to_string(), "/data/hello.txt".to_string()))
+            .output(TesOutput::matching(
+                "s3://bucket/out/".to_string(),
+                "/data/*.txt".to_string(),
+                "/data/".to_string(),
+            ))
+            .executor(
+                TesExecutorBuilder::new("alpine".to_string())
+                    .command(vec!["cat".to_string(), "/data/hello.txt".to_string()])
+                    .env("LANG".to_string(), "C".to_string()),
+            )
+            .ram_gb(2.0)
+            .zone("eu-west-1".to_string())
+            .build()
+            .expect("Failed to build task");
+
+        let json = serde_json::to_value(&task).unwrap();
+        assert_eq!(json["inputs"][0]["content"], "hello");
+        assert_eq!(json["outputs"][0]["path_prefix"], "/data/");
+        assert_eq!(json["executors"][0]["env"]["LANG"], "C");
+        assert_eq!(json["resources"]["ram_gb"], 2.0);
+        assert_eq!(json["resources"]["zones"][0], "eu-west-1");
+    }
+
+    #[test]
+    fn test_build_invalid_task() {
+        let result = TesTaskBuilder::new()
+            .input(TesInput::from_url("s3://bucket/a".to_string(), "a".to_string()))
+            .build();
+        match result {
+            Err(Error::Validation(violations)) => {
+                let paths: Vec<&str> = violations.iter().map(|violation| violation.path.as_str()).collect();
+                assert_eq!(paths, vec!["executors", "inputs[0].path"]);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_build_task_with_local_input() {
+        let executor = TesExecutorBuilder::new("alpine".to_string()).command(vec!["true".to_string()]);
+        let task = TesTaskBuilder::new()
+            .input(TesInput::from_url("data/reads.fq".to_string(), "/data/reads.fq".to_string()))
+            .executor(executor.clone())
+            .build()
+            .expect("local inputs are left to staging");
+        assert!(matches!(task.validate(), Err(violations) if violations[0].path == "inputs[0].url"));
+
+        // other violations of local inputs are still reported
+        let result = TesTaskBuilder::new()
+            .input(TesInput::from_url("data/reads.fq".to_string(), "reads.fq".to_string()))
+            .executor(executor)
+            .build();
+        assert!(matches!(result, Err(Error::Validation(violations)) if violations.len() == 1));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::clients::tes::models::{ListTasksParams, TesTask, TesView};
+use crate::clients::tes::{Task, TES};
+use crate::Result;
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+/// The number of tasks `TES::cancel_where` cancels at the same time.
+const CANCEL_CONCURRENCY: usize = 8;
+
+impl TES {
+    /// Creates several tasks, with at most `concurrency` requests in flight.
+    ///
+    /// Each task is validated and created as with `create`; a failure only affects its own
+    /// result.
+    ///
+    /// # Arguments
+    /// - `tasks`: The tasks to create.
+    /// - `concurrency`: The maximum number of tasks created at the same time, at least 1.
+    ///
+    /// # Returns
+    /// - The result of each task, in the order of `tasks`.
+    pub async fn create_many(&self, tasks: Vec<TesTask>, concurrency: usize) -> Vec<Result<Task>> {
+        stream::iter(tasks)
+            .map(|task| self.create(task))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Cancels every task matching a filter that is not finished yet.
+    ///
+    /// All the matching tasks are listed, following the page tokens, before the first one is
+    /// cancelled, so that cancellations do not move tasks between pages.
+    ///
+    /// # Arguments
+    /// - `filter`: The name prefix, state and tags of the tasks to cancel; its `view` and
+    ///   `page_token` are ignored.
+    ///
+    /// # Returns
+    /// - The ID of each task that was not finished, with the result of its cancellation, or
+    ///   the error of a failed list request, in which case no task is cancelled.
+    pub async fn cancel_where(
+        &self,
+        filter: ListTasksParams,
+    ) -> Result<Vec<(String, Result<serde_json::Value>)>> {
+        let params = ListTasksParams {
+            view: Some(TesView::Minimal),
+            page_token: None,
+            ..filter
+        };
+        let tasks: Vec<TesTask> = self.list_all_tasks(Some(params), None).try_collect().await?;
+        let ids: Vec<String> = tasks
+            .into_iter()
+            .filter(|task| !task.state.unwrap_or_default().is_terminal())
+            .filter_map(|task| task.id)
+            .collect();
+
+        let results = stream::iter(ids)
+            .map(|id| async move {
+                let result = Task::new(id.clone(), self.transport.clone()).cancel().await;
+                (id, result)
+            })
+            .buffered(CANCEL_CONCURRENCY)
+            .collect()
+            .await;
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clients::tes::models::{ListTasksParams, TesExecutor, TesTask, TesView};
+    use crate::clients::tes::TES;
+    use crate::utils::configuration::Configuration;
+    use crate::utils::transport::Transport;
+    use crate::Error;
+    use mockito::{mock, Matcher};
+
+    fn tes() -> TES {
+        let config = Configuration::new(url::Url::parse(&mockito::server_url()).unwrap());
+        let transport = Transport::new(&config).unwrap();
+        TES::for_test(config, transport)
+    }
+
+    #[tokio::test]
+    async fn test_create_many() {
+        let m = mock("POST", "/ga4gh/tes/v1/tasks")
+            .match_body(Matcher::PartialJsonString(r#"{"name": "bulk"}"#.to_string()))
+            .with_status(200)
+            .with_body(r#"{"id": "bulk-1"}"#)
+            .expect(2)
+            .create();
+
+        let valid = TesTask {
+            name: Some("bulk".to_string()),
+            ..TesTask::new(vec![TesExecutor::new("alpine".to_string(), vec!["true".to_string()])])
+        };
+        let tasks = vec![valid.clone(), TesTask::default(), valid];
+
+        let results = tes().create_many(tasks, 2).await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().id, "bulk-1");
+        assert!(matches!(results[1], Err(Error::Validation(_))));
+        assert_eq!(results[2].as_ref().unwrap().id, "bulk-1");
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_cancel_where() {
+        let first = mock("GET", "/ga4gh/tes/v1/tasks")
+            .match_query(Matcher::Exact("name_prefix=cohort-&tag_key=run&view=MINIMAL".to_string()))
+            .with_status(200)
+            .with_body(r#"{"tasks": [{"id": "c1", "state": "RUNNING"}, {"id": "c2", "state": "COMPLETE"}], "next_page_token": "p2"}"#)
+            .create();
+        let second = mock("GET", "/ga4gh/tes/v1/tasks")
+            .match_query(Matcher::Exact(
+                "name_prefix=cohort-&page_token=p2&tag_key=run&view=MINIMAL".to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"tasks": [{"id": "c3", "state": "QUEUED"}]}"#)
+            .create();
+        let cancel_c1 = mock("POST", "/ga4gh/tes/v1/tasks/c1:cancel").with_status(200).with_body("{}").create();
+        let cancel_c3 = mock("POST", "/ga4gh/tes/v1/tasks/c3:cancel").with_status(404).create();
+
+        let filter = ListTasksParams {
+            name_prefix: Some("cohort-".to_string()),
+            view: Some(TesView::Full),
+            ..Default::default()
+        }
+        .with_tag("run".to_string(), String::new());
+        let results = tes().cancel_where(filter).await.expect("Failed to list tasks");
+
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["c1", "c3"]);
+        assert!(results[0].1.is_ok());
+        assert_eq!(results[1].1.as_ref().unwrap_err().status().map(|s| s.as_u16()), Some(404));
+        for m in [first, second, cancel_c1, cancel_c3] {
+            m.assert();
+        }
+    }
+}
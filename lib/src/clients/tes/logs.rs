@@ -0,0 +1,243 @@
+use crate::clients::tes::models::{TesState, TesTask, TesTaskLog, TesView};
+use crate::clients::tes::wait::{Observer, Poller, WaitOptions};
+use crate::clients::tes::Task;
+use crate::utils::telemetry::{operation_span, traced};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use futures::stream::Stream;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// An output stream of an executor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl fmt::Display for LogStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogStream::Stdout => write!(f, "stdout"),
+            LogStream::Stderr => write!(f, "stderr"),
+        }
+    }
+}
+
+/// Log text of an executor that appeared since the previous chunk of the same stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogChunk {
+    /// The index of the attempt in the task logs; servers add an attempt on each retry.
+    pub attempt: usize,
+    /// The index of the executor in the task.
+    pub executor: usize,
+    /// The stream the text was written to.
+    pub stream: LogStream,
+    /// The new text.
+    pub text: String,
+}
+
+impl Task {
+    /// Fetches the logs of the task in `FULL` view.
+    ///
+    /// # Returns
+    /// - On success, returns the logs of each attempt to run the task, oldest first. Each holds
+    ///   the logs of the executors that ran, with their exit codes, `stdout` and `stderr`, and
+    ///   the system logs of the attempt. The list is empty if the task has not started.
+    /// - On failure, returns an error.
+    pub async fn logs(&self) -> Result<Vec<TesTaskLog>> {
+        let span = operation_span("tes", "get_task_logs");
+        span.record("task_id", self.id.as_str());
+        traced(span, async {
+            let task = self.get(TesView::Full).await?;
+            Ok(task.logs.unwrap_or_default())
+        })
+        .await
+    }
+
+    /// Follows the `stdout` and `stderr` of the executors of the latest attempt, polling the
+    /// task in `FULL` view.
+    ///
+    /// The stream yields the text appended to each executor stream since the previous poll.
+    /// When a server truncates a log instead of appending to it, the whole new text is yielded.
+    /// When the server retries the task, the stream moves on to the new attempt. The stream
+    /// ends after the poll that finds the task in a terminal state, or after yielding the error
+    /// of a failed request, `Error::WaitTimeout` or, when the cancellation token is cancelled,
+    /// `Error::Cancelled`.
+    ///
+    /// # Arguments
+    /// - `options`: The polling intervals, timeout and cancellation token. The interval goes
+    ///   back to its initial value whenever new text appears.
+    ///
+    /// # Returns
+    /// - A stream of `LogChunk`s, in the order of executors and streams on each poll.
+    pub fn follow_logs(&self, options: WaitOptions) -> impl Stream<Item = Result<LogChunk>> + 'static {
+        let follow = Follow {
+            task: self.clone(),
+            last_state: TesState::Unknown,
+            seen: HashMap::new(),
+        };
+        Poller::new(follow, options).into_stream()
+    }
+}
+
+/// The task of `Task::follow_logs` and the log text seen so far.
+struct Follow {
+    task: Task,
+    last_state: TesState,
+    seen: HashMap<(usize, usize, LogStream), String>,
+}
+
+#[async_trait]
+impl Observer for Follow {
+    type Observation = TesTask;
+    type Item = LogChunk;
+
+    async fn poll(&self) -> Result<TesTask> {
+        self.task.get(TesView::Full).await
+    }
+
+    /// Queues the text that appeared in the logs of the latest attempt since the previous poll.
+    fn observe(&mut self, task: TesTask, chunks: &mut VecDeque<LogChunk>) -> bool {
+        self.last_state = task.state.unwrap_or_default();
+        let logs = task.logs.unwrap_or_default();
+        let attempt = logs.len().saturating_sub(1);
+        for (executor, executor_log) in logs.into_iter().skip(attempt).flat_map(|log| log.logs).enumerate() {
+            let streams = [
+                (LogStream::Stdout, executor_log.stdout),
+                (LogStream::Stderr, executor_log.stderr),
+            ];
+            for (stream, text) in streams {
+                let Some(text) = text else { continue };
+                let previous = self.seen.entry((attempt, executor, stream)).or_default();
+                let new_text = match text.strip_prefix(previous.as_str()) {
+                    Some(appended) => appended.to_string(),
+                    None => text.clone(),
+                };
+                *previous = text;
+                if !new_text.is_empty() {
+                    chunks.push_back(LogChunk {
+                        attempt,
+                        executor,
+                        stream,
+                        text: new_text,
+                    });
+                }
+            }
+        }
+        self.last_state.is_terminal()
+    }
+
+    fn timeout_error(&self) -> Error {
+        Error::WaitTimeout {
+            task_id: self.task.id.clone(),
+            last_state: self.last_state,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::tes::test_backend::{fast, sequence_task};
+    use futures::StreamExt;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn task(documents: Vec<Value>) -> Task {
+        sequence_task(documents).0
+    }
+
+    fn document(state: &str, attempts: Value) -> Value {
+        json!({"id": "123", "state": state, "executors": [], "logs": attempts})
+    }
+
+    #[tokio::test]
+    async fn test_logs() {
+        let task = task(vec![document(
+            "EXECUTOR_ERROR",
+            json!([{
+                "logs": [{"exit_code": 0, "stdout": "done\n"}, {"exit_code": 2, "stderr": "not found\n"}],
+                "outputs": [],
+                "system_logs": ["executor 1 failed"]
+            }]),
+        )]);
+
+        let logs = task.logs().await.expect("Failed to get logs");
+        assert_eq!(logs.len(), 1);
+        let exit_codes: Vec<i32> = logs[0].logs.iter().map(|log| log.exit_code).collect();
+        assert_eq!(exit_codes, vec![0, 2]);
+        assert_eq!(logs[0].logs[1].stderr.as_deref(), Some("not found\n"));
+        assert_eq!(logs[0].system_logs, Some(vec!["executor 1 failed".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_follow_logs() {
+        let task = task(vec![
+            document("QUEUED", json!([])),
+            document("RUNNING", json!([{"logs": [{"exit_code": 0, "stdout": "a\n"}], "outputs": []}])),
+            document("RUNNING", json!([{"logs": [{"exit_code": 0, "stdout": "a\nb\n"}], "outputs": []}])),
+            document(
+                "COMPLETE",
+                json!([{
+                    "logs": [{"exit_code": 0, "stdout": "c\n"}, {"exit_code": 0, "stderr": "warning\n"}],
+                    "outputs": []
+                }]),
+            ),
+        ]);
+
+        let chunks: Vec<(usize, LogStream, String)> = task
+            .follow_logs(fast())
+            .map(|chunk| chunk.expect("Failed to follow logs"))
+            .map(|chunk| (chunk.executor, chunk.stream, chunk.text))
+            .collect()
+            .await;
+        assert_eq!(
+            chunks,
+            vec![
+                (0, LogStream::Stdout, "a\n".to_string()),
+                (0, LogStream::Stdout, "b\n".to_string()),
+                // the log was truncated
+                (0, LogStream::Stdout, "c\n".to_string()),
+                (1, LogStream::Stderr, "warning\n".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_follow_logs_latest_attempt() {
+        let task = task(vec![
+            document("RUNNING", json!([{"logs": [{"exit_code": 0, "stdout": "first\n"}], "outputs": []}])),
+            document(
+                "COMPLETE",
+                json!([
+                    {"logs": [{"exit_code": 1, "stdout": "first\nfailed\n"}], "outputs": []},
+                    {"logs": [{"exit_code": 0, "stdout": "second\n"}], "outputs": []}
+                ]),
+            ),
+        ]);
+
+        let chunks: Vec<(usize, String)> = task
+            .follow_logs(fast())
+            .map(|chunk| chunk.expect("Failed to follow logs"))
+            .map(|chunk| (chunk.attempt, chunk.text))
+            .collect()
+            .await;
+        assert_eq!(chunks, vec![(0, "first\n".to_string()), (1, "second\n".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_follow_logs_timeout() {
+        let task = task(vec![document("RUNNING", json!([]))]);
+
+        let items: Vec<Result<LogChunk>> = task
+            .follow_logs(fast().with_timeout(Duration::from_millis(50)))
+            .collect()
+            .await;
+        assert_eq!(items.len(), 1);
+        assert!(matches!(
+            &items[0],
+            Err(Error::WaitTimeout { task_id, last_state: TesState::Running }) if task_id == "123"
+        ));
+    }
+}
@@ -24,12 +24,14 @@
 /// ```rust
 /// use ga4gh_sdk::clients::tes::TES;
 /// use ga4gh_sdk::utils::configuration::Configuration;
-/// use ga4gh_sdk::clients::tes::models::TesTask;
+/// use ga4gh_sdk::clients::tes::builder::{TesExecutorBuilder, TesTaskBuilder};
 ///
 /// # async fn test_tes() -> Result<(), Box<dyn std::error::Error>> {
 /// let config = Configuration::new(url::Url::parse("http://example.com")?);
 /// let tes = TES::new(&config).await?;
-/// let task = TesTask::default();
+/// let task = TesTaskBuilder::new()
+///     .executor(TesExecutorBuilder::new("alpine".to_string()).command(vec!["echo".to_string(), "hello".to_string()]))
+///     .build()?;
 /// let result = tes.create(task).await?;
 /// assert_eq!(result.id, "123");
 /// # Ok(())
@@ -46,7 +48,7 @@
 ///
 /// # async fn test_task_status() -> Result<(), Box<dyn std::error::Error>> {
 /// let config = Configuration::new(url::Url::parse("http://example.com")?);
-/// let transport = Transport::new(&config);
+/// let transport = Transport::new(&config)?;
 /// let task = Task::new("123".to_string(), transport);
 /// let result = task.status().await?;
 /// assert_eq!(result, TesState::Complete);
@@ -63,7 +65,7 @@
 ///
 /// # async fn test_task_cancel() -> Result<(), Box<dyn std::error::Error>> {
 /// let config = Configuration::new(url::Url::parse("http://example.com")?);
-/// let transport = Transport::new(&config);
+/// let transport = Transport::new(&config)?;
 /// let task = Task::new("123".to_string(), transport);
 /// let result = task.cancel().await?;
 /// assert_eq!(result["status"], "CANCELLED");
@@ -86,35 +88,36 @@
 /// # Ok(())
 /// # }
 /// ```
+pub mod builder;
+pub mod bulk;
+pub mod logs;
 pub mod models;
+pub mod staging;
+pub mod template;
+#[cfg(test)]
+mod test_backend;
+pub mod validation;
+pub mod wait;
+pub mod watch;
 use crate::utils::configuration::Configuration;
 use crate::clients::serviceinfo::models::Service;
+use crate::clients::routes::{encode_segment, Routes};
 use crate::clients::serviceinfo::ServiceInfo;
 use crate::clients::tes::models::TesListTasksResponse;
 use crate::clients::tes::models::TesState;
+use crate::clients::tes::models::TesServiceInfo;
 use crate::clients::tes::models::TesTask;
+use crate::clients::tes::models::TesView;
+use crate::utils::telemetry::{operation_span, traced};
 use crate::utils::transport::Transport;
 use crate::clients::tes::models::ListTasksParams;
+use crate::clients::tes::validation::Violation;
+use crate::error::from_json;
+use crate::{Error, Result};
+use futures::stream::{self, Stream, StreamExt};
+use tracing::warn;
 use serde_json;
-use serde_json::from_str;
 use serde_json::json;
-use serde::Serialize;
-use serde_json::Value;
-use log::{debug, error};
-
-/// Serializes any serializable item into a JSON `Value`.
-///
-/// # Arguments
-/// - `item`: The item to serialize.
-///
-/// # Returns
-/// - A `serde_json::Value` containing the serialized JSON.
-///
-/// # Panics
-/// - If serialization fails, the function will panic.
-fn serialize_to_json<T: Serialize>(item: T) -> Value {
-    serde_json::to_value(&item).unwrap()
-}
 
 /// URL-encodes a string.
 ///
@@ -127,6 +130,16 @@ pub fn urlencode<T: AsRef<str>>(s: T) -> String {
     ::url::form_urlencoded::byte_serialize(s.as_ref().as_bytes()).collect()
 }
 
+/// Returns the routes of the TES API for the configuration of the given transport.
+fn routes(transport: &Transport) -> Routes {
+    Routes::for_service(&transport.config, crate::clients::ServiceType::TES)
+}
+
+/// Returns the URL of a task, with its ID percent-encoded.
+fn task_url(transport: &Transport, id: &str, action: &str) -> url::Url {
+    routes(transport).url(&format!("tasks/{}{}", encode_segment(id), action))
+}
+
 #[derive(Debug, Clone)]
 pub struct Task {
     /// The unique ID of the task.
@@ -148,27 +161,35 @@ impl Task {
         Task { id, transport }
     }
 
+    /// Fetches the task.
+    ///
+    /// # Arguments
+    /// - `view`: The level of detail to include in the response.
+    ///
+    /// # Returns
+    /// - On success, returns the `TesTask`.
+    /// - On failure, returns an error.
+    pub async fn get(&self, view: TesView) -> Result<TesTask> {
+        let url = task_url(&self.transport, &self.id, "");
+        let params = json!({ "view": view });
+        let resp_str = self.transport.get(url.as_str(), Some(params)).await?;
+        from_json(&resp_str)
+    }
+
     /// Fetches the current status of the task.
     ///
     /// # Returns
-    /// - On success, returns a `TesState` representing the task state.
+    /// - On success, returns a `TesState` representing the task state, `UNKNOWN` if the
+    ///   server did not report one.
     /// - On failure, returns an error.
-    pub async fn status(&self) -> Result<TesState, Box<dyn std::error::Error>> {
-        let task_id = &self.id;
-        let view = "FULL";
-        let url = format!("/tasks/{}?view={}", task_id, view);
-        let response = self.transport.get(&url, None).await;
-        match response {
-            Ok(resp_str) => {
-                let task: TesTask = from_str(&resp_str)?;
-                Ok(task.state.unwrap())
-            }
-            Err(e) => {
-                let err_msg = format!("HTTP request failed: {}", e);
-                eprintln!("{}", err_msg);
-                Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, err_msg)))
-            }
-        }
+    pub async fn status(&self) -> Result<TesState> {
+        let span = operation_span("tes", "get_task_status");
+        span.record("task_id", self.id.as_str());
+        traced(span, async {
+            let task = self.get(TesView::Full).await?;
+            Ok(task.state.unwrap_or_default())
+        })
+        .await
     }
 
     /// Cancels the task.
@@ -176,21 +197,15 @@ impl Task {
     /// # Returns
     /// - On success, returns a `serde_json::Value` containing the server's response.
     /// - On failure, returns an error.
-    pub async fn cancel(&self) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
-        let id = &self.id;
-        let id = &urlencode(id);
-        let url = format!("/tasks/{}:cancel", id);
-        let response = self.transport.post(&url, None).await;
-        match response {
-	    Ok(resp_str) => {
-	        let parsed_json = serde_json::from_str::<serde_json::Value>(&resp_str);
-	        match parsed_json {
-	            Ok(json) => Ok(json),
-	            Err(e) => Err(format!("Failed to parse JSON: {}", e).into()),
-	        }
-	    }
-	    Err(e) => Err(format!("HTTP request failed: {}", e).into()),
-	}
+    pub async fn cancel(&self) -> Result<serde_json::Value> {
+        let span = operation_span("tes", "cancel_task");
+        span.record("task_id", self.id.as_str());
+        traced(span, async {
+            let url = task_url(&self.transport, &self.id, ":cancel");
+            let resp_str = self.transport.post(url.as_str(), None).await?;
+            from_json::<serde_json::Value>(&resp_str)
+        })
+        .await
     }
 }
 
@@ -199,7 +214,9 @@ impl Task {
 pub struct TES {
     #[allow(dead_code)]
     pub config: Configuration, // not used yet
-    pub service: Result<Service, Box<dyn std::error::Error>>,
+    pub service: Result<Service>,
+    /// The TES-specific service information, if the service returned a valid one.
+    pub service_info: Option<TesServiceInfo>,
     pub transport: Transport,
 }
 
@@ -211,16 +228,37 @@ impl TES {
     ///
     /// # Returns
     /// - A new `TES` instance, or an error if the initialization fails.
-    pub async fn new(config: &Configuration) -> Result<Self, Box<dyn std::error::Error>> {
-        let transport = Transport::new(config);
-        let service_info = ServiceInfo::new(config)?;
+    pub async fn new(config: &Configuration) -> Result<Self> {
+        TES::from_transport(Transport::new(config)?).await
+    }
+
+    /// Creates a new `TES` instance sending requests through the given transport.
+    ///
+    /// Use this to run the client against a custom `HttpBackend`.
+    ///
+    /// # Arguments
+    /// - `transport`: The transport for sending HTTP requests.
+    ///
+    /// # Returns
+    /// - A new `TES` instance, or an error if the initialization fails.
+    pub async fn from_transport(transport: Transport) -> Result<Self> {
+        let service_info = ServiceInfo::from_transport(transport.clone()).with_routes(routes(&transport));
 
         let resp = service_info.get().await;
+        let tes_service_info = match resp {
+            Ok(_) => service_info
+                .get_as::<TesServiceInfo>()
+                .await
+                .map_err(|e| warn!("Ignoring the TES service information: {}", e))
+                .ok(),
+            Err(_) => None,
+        };
 
         let instance = TES {
-            config: config.clone(),
+            config: transport.config.clone(),
             transport,
             service: resp,
+            service_info: tes_service_info,
         };
 
         instance.check()?; // Propagate the error if check() fails
@@ -231,13 +269,31 @@ impl TES {
     ///
     /// # Returns
     /// - Ok(()) if the service is valid.
-    /// - Err(String) if the service is invalid or an error occurs.
-    fn check(&self) -> Result<(), String> {
+    /// - Err(Error::Configuration) if the service is invalid or an error occurs.
+    fn check(&self) -> Result<()> {
         let resp = &self.service;
         match resp.as_ref() {
             Ok(service) if service.r#type.artifact == "tes" => Ok(()),
-            Ok(_) => Err("The endpoint is not an instance of TES".into()),
-            Err(_) => Err("Error accessing the service".into()),
+            Ok(_) => Err(Error::Configuration("The endpoint is not an instance of TES".to_string())),
+            Err(e) => Err(Error::Configuration(format!("Error accessing the service: {}", e))),
+        }
+    }
+
+    /// Creates a `TES` instance for a TES service without service information, without
+    /// contacting the service.
+    #[cfg(test)]
+    pub(crate) fn for_test(config: Configuration, transport: Transport) -> Self {
+        TES {
+            config,
+            service: Ok(Service {
+                r#type: Box::new(crate::clients::serviceinfo::models::ServiceType {
+                    artifact: "tes".to_string(),
+                    ..Default::default()
+                }),
+                ..Service::default()
+            }),
+            service_info: None,
+            transport,
         }
     }
 
@@ -247,66 +303,95 @@ impl TES {
     /// - `task`: The `TesTask` to create.
     ///
     /// # Returns
-    /// - On success, returns a `Task` containing the created task details.
-    /// - On failure, returns an error.
+    /// - On success, returns a `Task` containing the created task details. Local inputs are
+    ///   staged first if the configuration sets `staging`; see `TES::stage_inputs`.
+    /// - On failure, returns an error, `Error::Validation` without contacting the service if
+    ///   the task violates the specification or uses backend parameters the service rejects.
+    ///   Capabilities the service may lack are logged as warnings; use `TES::create_checked`
+    ///   to get them instead.
     pub async fn create(
         &self,
         task: TesTask, /*, params: models::TesTask*/
-    ) -> Result<Task, Box<dyn std::error::Error>> {
-        // First, check if the service is of TES class
-        self.check().map_err(|e| {
-            log::error!("Service check failed: {}", e);
-            e
-        })?;
-        let response = self
-            .transport
-            .post("/ga4gh/tes/v1/tasks", Some(json!(task)))
-            .await;
-        match response {
-            Ok(response_body) => {
-                let v: serde_json::Value = serde_json::from_str(&response_body)?;
-
-                // Access the `id` field
-                let task_id = v
-                    .get("id")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or_default()
-                    .trim_matches('"')
-                    .to_string();
-
-                let task = Task {
-                    id: task_id,
-                    transport: self.transport.clone(),
-                };
-                Ok(task)
-            }
-            Err(e) => Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to post task: {}", e),
-            ))),
+    ) -> Result<Task> {
+        let (task, warnings) = self.create_checked(task).await?;
+        for warning in &warnings {
+            warn!("The task may not run as expected: {}", warning);
         }
+        Ok(task)
+    }
+
+    /// Creates a new TES task, returning the capabilities the service may lack along with it.
+    ///
+    /// # Arguments
+    /// - `task`: The `TesTask` to create.
+    ///
+    /// # Returns
+    /// - On success, returns the created `Task` and the warnings of
+    ///   `TesServiceInfo::check_task`, empty if the service returned no service information.
+    /// - On failure, returns an error, as for `TES::create`.
+    pub async fn create_checked(&self, task: TesTask) -> Result<(Task, Vec<Violation>)> {
+        let span = operation_span("tes", "create_task");
+        traced(span.clone(), async {
+            // First, check if the service is of TES class
+            self.check()?;
+            self.validate_unstaged(&task)?;
+            if let Some(service_info) = &self.service_info {
+                let errors = service_info.check_task(&task).errors;
+                if !errors.is_empty() {
+                    return Err(Error::Validation(errors));
+                }
+            }
+            // only upload the inputs of a task the service accepts
+            let task = self.stage_inputs(task).await?;
+            task.validate().map_err(Error::Validation)?;
+            let warnings = match &self.service_info {
+                Some(service_info) => service_info.check_task(&task).warnings,
+                None => Vec::new(),
+            };
+            let url = routes(&self.transport).url("tasks");
+            let response_body = self
+                .transport
+                .post(url.as_str(), Some(json!(task)))
+                .await?;
+            let v: serde_json::Value = from_json(&response_body)?;
+
+            // Access the `id` field
+            let task_id = v
+                .get("id")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .trim_matches('"')
+                .to_string();
+            span.record("task_id", task_id.as_str());
+
+            let task = Task {
+                id: task_id,
+                transport: self.transport.clone(),
+            };
+            Ok((task, warnings))
+        })
+        .await
     }
 
     /// Retrieves the details of a specific TES task.
     ///
     /// # Arguments
-    /// - `view`: The level of detail to include in the response (e.g., `FULL`).
+    /// - `view`: The level of detail to include in the response.
     /// - `id`: The task ID.
     ///
     /// # Returns
     /// - On success, returns a `TesTask` containing the task details.
     /// - On failure, returns an error.
-    pub async fn get(&self, view: &str, id: &str) -> Result<TesTask, Box<dyn std::error::Error>> {
-        let url = format!("/tasks/{}?view={}", id, view);
-        let response = self.transport.get(&url, None).await;
-
-        match response {
-            Ok(resp_str) => {
-                let task: TesTask = from_str(&resp_str)?;
-                Ok(task)
-            }
-            Err(e) => Err(e),
-        }
+    pub async fn get(&self, view: TesView, id: &str) -> Result<TesTask> {
+        let span = operation_span("tes", "get_task");
+        span.record("task_id", id);
+        traced(span, async {
+            let url = task_url(&self.transport, id, "");
+            let params = json!({ "view": view });
+            let resp_str = self.transport.get(url.as_str(), Some(params)).await?;
+            from_json(&resp_str)
+        })
+        .await
     }
 
     /// Lists TES tasks based on provided filtering parameters.
@@ -320,37 +405,68 @@ impl TES {
     pub async fn list_tasks(
         &self,
         params: Option<ListTasksParams>,
-    ) -> Result<TesListTasksResponse, Box<dyn std::error::Error>> {
-        let params_value = params.map(serialize_to_json);
-        // Make the request with or without parameters based on the presence of params
-        let response = if let Some(params_value) = params_value {
-            self.transport.get("/tasks", Some(params_value)).await
-        } else {
-            self.transport.get("/tasks", None).await
-        };
-        
-        match response {
-            Ok(resp_str) => {
-                let task: TesListTasksResponse = from_str(&resp_str)?;
-                Ok(task)
-            }
-            Err(e) => {
-                error!("HTTP request failed: {:?}", e);
-                Err(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("HTTP request failed: {:?}", e),
-                )))
-            }
+    ) -> Result<TesListTasksResponse> {
+        traced(operation_span("tes", "list_tasks"), async {
+            let params_value = params.as_ref().map(ListTasksParams::to_query).transpose()?;
+            let url = routes(&self.transport).url("tasks");
+            let resp_str = self.transport.get(url.as_str(), params_value).await?;
+            from_json(&resp_str)
+        })
+        .await
+    }
+
+    /// Lists all the TES tasks matching the filtering parameters, following the page tokens.
+    ///
+    /// Pages are only requested as the stream is consumed. The stream ends after the last page,
+    /// after `limit` tasks, or after yielding the error of a failed request.
+    ///
+    /// # Arguments
+    /// - `params`: Optional filtering parameters; `page_size` sets the size of each page and
+    ///   `page_token` the page to start from.
+    /// - `limit`: The maximum number of tasks to return, if any.
+    ///
+    /// # Returns
+    /// - A stream of the tasks.
+    pub fn list_all_tasks(
+        &self,
+        params: Option<ListTasksParams>,
+        limit: Option<usize>,
+    ) -> impl Stream<Item = Result<TesTask>> + '_ {
+        let mut params = params.unwrap_or_default();
+        if let Some(limit) = limit.and_then(|limit| i32::try_from(limit).ok()) {
+            // no need to fetch more than the limit in a single page
+            params.page_size = Some(params.page_size.map_or(limit, |size| size.min(limit)).max(1));
         }
+        let pages = stream::unfold(Some(params), move |params| async move {
+            let mut params = params?;
+            match self.list_tasks(Some(params.clone())).await {
+                Ok(page) => {
+                    let next = page.next_page_token.filter(|token| !token.is_empty()).map(|token| {
+                        params.page_token = Some(token);
+                        params
+                    });
+                    Some((Ok(page.tasks), next))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+        pages
+            .flat_map(|page| match page {
+                Ok(tasks) => stream::iter(tasks.into_iter().map(Ok).collect::<Vec<_>>()),
+                Err(e) => stream::iter(vec![Err(e)]),
+            })
+            .take(limit.unwrap_or(usize::MAX))
     }
 }
 #[cfg(test)]
 mod tests {
-    use crate::clients::serviceinfo::models::ServiceType;
 
     use super::*;
+    use crate::clients::tes::models::{TesExecutor, TesOutput, TesResources};
+    use crate::utils::backend::{HttpBackend, HttpRequest, HttpResponse, StatusCode};
     use mockito::mock;
     use mockito::server_url;
+    use std::sync::Arc;
 
 
     #[tokio::test]
@@ -362,34 +478,27 @@ mod tests {
 
         let mock_url = url::Url::parse(&server_url()).expect("Invalid URL");
         let config= Configuration::new(mock_url);
-        let transport = Transport::new(&config);
+        let transport = Transport::new(&config).unwrap();
         
-        let tes = TES {
-            config,
-            service: Ok(Service {
-                r#type: Box::new(ServiceType {
-                    artifact: "tes".to_string(),
-                    ..Default::default()
-                }),
-                ..Service::default()
-            }),
-            transport,
-        };
+        let tes = TES::for_test(config, transport);
 
-        let task = TesTask::default();
+        let task = TesTask::new(vec![TesExecutor::new("alpine".to_string(), vec!["true".to_string()])]);
         let result = tes.create(task).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap().id, String::from("123"));
+
+        let invalid = tes.create(TesTask::default()).await;
+        assert!(matches!(invalid, Err(Error::Validation(_))));
     }
 
     #[tokio::test]
     async fn test_task_status() {
-        let _m = mock("GET", "/tasks/123?view=FULL")
+        let _m = mock("GET", "/ga4gh/tes/v1/tasks/123?view=FULL")
             .with_status(200)
             .with_body(r#"{"state": "COMPLETE", "executors": []}"#)
             .create();
         let mock_url = url::Url::parse(&server_url()).expect("Invalid URL");
-        let transport = Transport::new(&Configuration::new(mock_url));
+        let transport = Transport::new(&Configuration::new(mock_url)).unwrap();
         let task = Task::new("123".to_string(), transport);
 
         let result = task.status().await;
@@ -397,15 +506,28 @@ mod tests {
         assert_eq!(result.unwrap(), TesState::Complete);
     }
 
+    #[tokio::test]
+    async fn test_task_status_missing_state() {
+        let _m = mock("GET", "/ga4gh/tes/v1/tasks/456?view=FULL")
+            .with_status(200)
+            .with_body(r#"{"id": "456", "executors": []}"#)
+            .create();
+        let mock_url = url::Url::parse(&server_url()).expect("Invalid URL");
+        let transport = Transport::new(&Configuration::new(mock_url)).unwrap();
+        let task = Task::new("456".to_string(), transport);
+
+        assert_eq!(task.status().await.unwrap(), TesState::Unknown);
+    }
+
     #[tokio::test]
     async fn test_task_cancel() {
-        let _m = mock("POST", "/tasks/123:cancel")
+        let _m = mock("POST", "/ga4gh/tes/v1/tasks/123:cancel")
             .with_status(200)
             .with_body(r#"{"status": "CANCELLED", "executors": []}"#)
             .create();
 
         let mock_url = url::Url::parse(&server_url()).expect("Invalid URL");
-        let transport = Transport::new(&Configuration::new(mock_url));
+        let transport = Transport::new(&Configuration::new(mock_url)).unwrap();
 
         let task = Task::new("123".to_string(), transport);
 
@@ -416,43 +538,190 @@ mod tests {
 
     #[tokio::test]
     async fn test_tes_get() {
-        let _m = mock("GET", "/tasks/123?view=FULL")
+        let _m = mock("GET", "/ga4gh/tes/v1/tasks/123?view=FULL")
             .with_status(200)
             .with_body(r#"{"id": "123", "state": "COMPLETE", "executors": []}"#)
             .create();
 
         let mock_url = url::Url::parse(&server_url()).expect("Invalid URL");
         let config= Configuration::new(mock_url);
-        let transport = Transport::new(&config);
-        let tes = TES {
-            config,
-            service: Ok(Service::default()),
-            transport,
-        };
+        let transport = Transport::new(&config).unwrap();
+        let tes = TES::for_test(config, transport);
 
-        let result = tes.get("FULL", "123").await;
+        let result = tes.get(TesView::Full, "123").await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap().id, Some(String::from("123")));
     }
 
     #[tokio::test]
     async fn test_tes_list_tasks() {
-        let _m = mock("GET", "/tasks")
+        let _m = mock("GET", "/ga4gh/tes/v1/tasks")
             .with_status(200)
             .with_body(r#"{"tasks": []}"#)
             .create();
 
         let mock_url = url::Url::parse(&server_url()).expect("Invalid URL");
         let config= Configuration::new(mock_url);
-        let transport = Transport::new(&config);
-        let tes = TES {
-            config,
-            service: Ok(Service::default()),
-            transport,
-        };
+        let transport = Transport::new(&config).unwrap();
+        let tes = TES::for_test(config, transport);
 
         let result = tes.list_tasks(None).await;
         assert!(result.is_ok());
         assert!(result.unwrap().tasks.is_empty());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_tes_list_tasks_with_tags() {
+        let _m = mock("GET", "/ga4gh/tes/v1/tasks")
+            .match_query(mockito::Matcher::Exact(
+                "tag_key=foo1&tag_key=foo2&tag_value=&tag_value=bar2&view=BASIC".to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"tasks": []}"#)
+            .create();
+
+        let mock_url = url::Url::parse(&server_url()).expect("Invalid URL");
+        let config = Configuration::new(mock_url);
+        let transport = Transport::new(&config).unwrap();
+        let tes = TES::for_test(config, transport);
+
+        let params = ListTasksParams {
+            view: Some(TesView::Basic),
+            ..Default::default()
+        }
+        .with_tag("foo1".to_string(), String::new())
+        .with_tag("foo2".to_string(), "bar2".to_string());
+        assert!(tes.list_tasks(Some(params)).await.is_ok());
+
+        // without any value, only the keys are sent
+        let params = ListTasksParams::default().with_tag("foo1".to_string(), String::new());
+        assert_eq!(params.to_query().unwrap(), json!({"tag_key": ["foo1"]}));
+
+        let params = ListTasksParams {
+            tag_value: Some(vec!["bar".to_string()]),
+            ..Default::default()
+        };
+        assert!(matches!(tes.list_tasks(Some(params)).await, Err(Error::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_tes_list_all_tasks() {
+        let first = mock("GET", "/ga4gh/tes/v1/tasks")
+            .match_query(mockito::Matcher::Exact("page_size=2".to_string()))
+            .with_status(200)
+            .with_body(r#"{"tasks": [{"id": "1"}, {"id": "2"}], "next_page_token": "p2"}"#)
+            .expect(2)
+            .create();
+        let second = mock("GET", "/ga4gh/tes/v1/tasks")
+            .match_query(mockito::Matcher::Exact("page_size=2&page_token=p2".to_string()))
+            .with_status(200)
+            .with_body(r#"{"tasks": [{"id": "3"}], "next_page_token": ""}"#)
+            .expect(1)
+            .create();
+
+        let mock_url = url::Url::parse(&server_url()).expect("Invalid URL");
+        let config = Configuration::new(mock_url);
+        let transport = Transport::new(&config).unwrap();
+        let tes = TES::for_test(config, transport);
+
+        let params = ListTasksParams {
+            page_size: Some(2),
+            ..Default::default()
+        };
+        let ids: Vec<String> = tes
+            .list_all_tasks(Some(params), None)
+            .map(|task| task.expect("Failed to list tasks").id.unwrap())
+            .collect()
+            .await;
+        assert_eq!(ids, vec!["1", "2", "3"]);
+
+        // the limit caps the page size and stops before the second page
+        let limited: Vec<Result<TesTask>> = tes.list_all_tasks(None, Some(2)).collect().await;
+        assert_eq!(limited.len(), 2);
+        first.assert();
+        second.assert();
+    }
+
+    /// An in-memory TES serving a single task.
+    struct InMemoryTes;
+
+    #[async_trait::async_trait]
+    impl HttpBackend for InMemoryTes {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+            let body = match request.url.path() {
+                "/ga4gh/tes/v1/service-info" => json!({
+                    "id": "org.example.tes",
+                    "name": "In-memory TES",
+                    "type": {"group": "org.ga4gh", "artifact": "tes", "version": "1.1.0"},
+                    "organization": {"name": "Example", "url": "https://example.com"},
+                    "version": "1.0.0",
+                    "storage": ["s3://example-bucket"],
+                    "tesResources_backend_parameters": ["VmSize"]
+                }),
+                "/ga4gh/tes/v1/tasks/123" => json!({"id": "123", "state": "RUNNING", "executors": []}),
+                "/ga4gh/tes/v1/tasks" => json!({"id": "789"}),
+                _ => return Ok(HttpResponse::new(StatusCode::NOT_FOUND, "")),
+            };
+            Ok(HttpResponse::new(StatusCode::OK, body.to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tes_from_transport() {
+        let config = Configuration::new(url::Url::parse("http://tes.invalid").expect("Invalid URL"));
+        let transport = Transport::with_backend(&config, Arc::new(InMemoryTes)).unwrap();
+
+        let tes = TES::from_transport(transport).await.expect("Failed to create TES instance");
+        let task = tes.get(TesView::Full, "123").await.expect("Failed to get task");
+        assert_eq!(task.state, Some(TesState::Running));
+
+        let missing = tes.get(TesView::Full, "456").await;
+        assert_eq!(missing.unwrap_err().status(), Some(StatusCode::NOT_FOUND));
+
+        let service_info = tes.service_info.as_ref().expect("Missing TES service information");
+        assert_eq!(service_info.storage, Some(vec!["s3://example-bucket".to_string()]));
+
+        // rejected before it is sent, as the service would fail it
+        let mut task = TesTask::new(vec![TesExecutor::new("alpine".to_string(), vec!["true".to_string()])]);
+        task.resources = Some(Box::new(TesResources {
+            backend_parameters: Some([("Queue".to_string(), "fast".to_string())].into_iter().collect()),
+            backend_parameters_strict: Some(true),
+            ..TesResources::new()
+        }));
+        match tes.create(task).await {
+            Err(Error::Validation(violations)) => {
+                assert_eq!(violations[0].path, "resources.backend_parameters.Queue");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // sent, as the service ignores unsupported parameters unless strict
+        let mut task = TesTask::new(vec![TesExecutor::new("alpine".to_string(), vec!["true".to_string()])]);
+        task.outputs = Some(vec![TesOutput::new("gs://bucket/out".to_string(), "/data/out".to_string())]);
+        let (task, warnings) = tes.create_checked(task).await.expect("Failed to create task");
+        assert_eq!(task.id, "789");
+        let warnings: Vec<&str> = warnings.iter().map(|warning| warning.path.as_str()).collect();
+        assert_eq!(warnings, vec!["outputs[0].url"]);
+    }
+
+    #[tokio::test]
+    async fn test_task_routes_with_prefix() {
+        let _get = mock("GET", "/compute/prod/ga4gh/tes/v1/tasks/a%2Fb%20c?view=BASIC")
+            .with_status(200)
+            .with_body(r#"{"id": "a/b c", "state": "QUEUED", "executors": []}"#)
+            .create();
+        let _cancel = mock("POST", "/compute/prod/ga4gh/tes/v1/tasks/a%2Fb%20c:cancel")
+            .with_status(200)
+            .with_body("{}")
+            .create();
+
+        let mock_url = url::Url::parse(&format!("{}/compute", server_url())).expect("Invalid URL");
+        let config = Configuration::new(mock_url).with_path_prefix("prod".to_string());
+        let transport = Transport::new(&config).unwrap();
+        let tes = TES::for_test(config, transport.clone());
+
+        let task = tes.get(TesView::Basic, "a/b c").await.expect("Failed to get task");
+        assert_eq!(task.state, Some(TesState::Queued));
+        assert!(Task::new("a/b c".to_string(), transport).cancel().await.is_ok());
+    }
+}
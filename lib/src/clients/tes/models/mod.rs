@@ -32,6 +32,8 @@ pub mod tes_task;
 pub use self::tes_task::TesTask;
 pub mod tes_task_log;
 pub use self::tes_task_log::TesTaskLog;
+pub mod tes_view;
+pub use self::tes_view::TesView;
 pub mod tes_list_tasks_params;
 pub use self::tes_list_tasks_params::ListTasksParams;
 
@@ -1,7 +1,9 @@
 use crate::clients::tes::models;
+use crate::{Error, Result};
+use serde_json::Value;
 
 /// struct for passing parameters to the method [`list_tasks`]
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Clone, Debug, Default)]
 pub struct ListTasksParams {
     /// OPTIONAL. Filter the list to include tasks where the name matches this prefix. If unspecified, no task name filtering is done.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -23,5 +25,48 @@ pub struct ListTasksParams {
     pub page_token: Option<String>,
     /// OPTIONAL. Affects the fields included in the returned Task messages.  `MINIMAL`: Task message will include ONLY the fields: - `tesTask.Id` - `tesTask.State`  `BASIC`: Task message will include all fields EXCEPT: - `tesTask.ExecutorLog.stdout` - `tesTask.ExecutorLog.stderr` - `tesInput.content` - `tesTaskLog.system_logs`  `FULL`: Task message includes all fields.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub view: Option<String>,
+    pub view: Option<models::TesView>,
+}
+
+impl ListTasksParams {
+    /// Adds a tag filter. An empty value matches any value of the tag.
+    ///
+    /// # Arguments
+    /// - `key`: The tag key.
+    /// - `value`: The tag value, or an empty string to match any value.
+    pub fn with_tag(mut self, key: String, value: String) -> Self {
+        let keys = self.tag_key.get_or_insert_with(Vec::new);
+        let values = self.tag_value.get_or_insert_with(Vec::new);
+        values.resize(keys.len(), String::new());
+        keys.push(key);
+        values.push(value);
+        self
+    }
+
+    /// Returns the query parameters of the request, with the repeated `tag_key` and
+    /// `tag_value` parameters aligned so that the server zips them into the right pairs.
+    ///
+    /// A key without a value gets an empty `tag_value`, and `tag_value` is left out
+    /// altogether when no tag has a value, e.g. `?tag_key=foo1`.
+    ///
+    /// # Returns
+    /// - The parameters as a JSON object, or `Error::InvalidInput` if there are more tag
+    ///   values than tag keys.
+    pub fn to_query(&self) -> Result<Value> {
+        let mut params = self.clone();
+        let keys = params.tag_key.as_deref().unwrap_or_default();
+        let mut values = params.tag_value.take().unwrap_or_default();
+        if values.len() > keys.len() {
+            return Err(Error::InvalidInput(format!(
+                "{} tag values given for {} tag keys",
+                values.len(),
+                keys.len()
+            )));
+        }
+        if values.iter().any(|value| !value.is_empty()) {
+            values.resize(keys.len(), String::new());
+            params.tag_value = Some(values);
+        }
+        serde_json::to_value(&params).map_err(|e| Error::InvalidInput(e.to_string()))
+    }
 }
@@ -10,6 +10,8 @@
 
 #![allow(unused_imports)]
 #![allow(clippy::empty_docs)]
+#![allow(clippy::derivable_impls)]
+#![allow(clippy::too_many_arguments)]
 use crate::clients::tes::models;
 use serde::{Deserialize, Serialize};
 
@@ -63,7 +65,7 @@ impl TesTask {
             inputs: None,
             outputs: None,
             resources: None,
-            executors: None,
+            executors: Some(executors),
             volumes: None,
             tags: None,
             logs: None,
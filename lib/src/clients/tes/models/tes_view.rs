@@ -0,0 +1,43 @@
+use crate::Error;
+
+/// TesView : The level of detail of the task documents returned by `GetTask` and `ListTasks`.
+///  - `MINIMAL`: only `id` and `state`.
+///  - `BASIC`: all fields except the executors' `stdout` and `stderr`, the inputs' `content` and the `system_logs`.
+///  - `FULL`: all fields.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum TesView {
+    #[default]
+    #[serde(rename = "MINIMAL")]
+    Minimal,
+    #[serde(rename = "BASIC")]
+    Basic,
+    #[serde(rename = "FULL")]
+    Full,
+}
+
+impl std::fmt::Display for TesView {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Minimal => write!(f, "MINIMAL"),
+            Self::Basic => write!(f, "BASIC"),
+            Self::Full => write!(f, "FULL"),
+        }
+    }
+}
+
+impl std::str::FromStr for TesView {
+    type Err = Error;
+
+    /// Parses a view name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "MINIMAL" => Ok(Self::Minimal),
+            "BASIC" => Ok(Self::Basic),
+            "FULL" => Ok(Self::Full),
+            _ => Err(Error::InvalidInput(format!(
+                "unknown view `{}`, expected MINIMAL, BASIC or FULL",
+                s
+            ))),
+        }
+    }
+}
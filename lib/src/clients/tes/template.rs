@@ -0,0 +1,315 @@
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The variables substituted for the `${VAR}` and `${VAR:-default}` placeholders of a task.
+///
+/// A variable is looked up in the variables set with `with_var`, then in the variables files in
+/// reverse order of addition, then, unless disabled, in the environment. As in a shell,
+/// `${VAR:-default}` uses the default when the variable is undefined or empty. Placeholders
+/// may appear anywhere in any string of the task.
+///
+/// In strict mode, the default, a placeholder without a value fails the substitution with
+/// `Error::UndefinedVariables`, listing every such placeholder. Otherwise it is left as it is.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::clients::tes::models::TesTask;
+/// use ga4gh_sdk::clients::tes::template::TemplateVars;
+///
+/// let task: TesTask = serde_json::from_str(
+///     r#"{"executors": [{"image": "alpine:${TAG:-3.20}", "command": ["cat", "${INPUT}"]}]}"#,
+/// ).unwrap();
+/// let vars = TemplateVars::new().with_var("INPUT".to_string(), "/data/input.txt".to_string());
+/// let task = vars.apply(&task).unwrap();
+/// let executor = &task.executors.unwrap()[0];
+/// assert_eq!(executor.image, "alpine:3.20");
+/// assert_eq!(executor.command[1], "/data/input.txt");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TemplateVars {
+    vars: HashMap<String, String>,
+    file_vars: HashMap<String, String>,
+    use_env: bool,
+    strict: bool,
+}
+
+impl Default for TemplateVars {
+    fn default() -> Self {
+        TemplateVars {
+            vars: HashMap::new(),
+            file_vars: HashMap::new(),
+            use_env: true,
+            strict: true,
+        }
+    }
+}
+
+impl TemplateVars {
+    /// Creates a strict set of variables backed by the environment.
+    pub fn new() -> Self {
+        TemplateVars::default()
+    }
+
+    /// Sets a variable, overriding the variables files and the environment.
+    pub fn with_var(mut self, key: String, value: String) -> Self {
+        self.vars.insert(key, value);
+        self
+    }
+
+    /// Sets a variable from a `KEY=VALUE` assignment.
+    ///
+    /// # Returns
+    /// - The variables, or `Error::InvalidInput` if the assignment has no `=`.
+    pub fn with_assignment(self, assignment: &str) -> Result<Self> {
+        let (key, value) = assignment.split_once('=').ok_or_else(|| {
+            Error::InvalidInput(format!("expected KEY=VALUE, got `{}`", assignment))
+        })?;
+        Ok(self.with_var(key.trim().to_string(), value.to_string()))
+    }
+
+    /// Reads variables from a file, overriding the files added before and the environment.
+    ///
+    /// Files ending in `.json` hold a JSON object and files ending in `.yaml` or `.yml` a YAML
+    /// mapping, both with scalar values. Any other file is read as a `.env` file: `KEY=VALUE`
+    /// lines, optionally prefixed with `export` and with quoted values, and `#` comments.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the variables file.
+    ///
+    /// # Returns
+    /// - The variables, or an error if the file cannot be read or parsed.
+    pub fn with_file(mut self, path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        let vars = match extension {
+            "json" => {
+                let value: Value = serde_json::from_str(&content)
+                    .map_err(|e| Error::InvalidInput(format!("invalid variables file {}: {}", path.display(), e)))?;
+                scalar_map(value, path)?
+            }
+            "yaml" | "yml" => {
+                let value: Value = serde_yaml::from_str(&content)
+                    .map_err(|e| Error::InvalidInput(format!("invalid variables file {}: {}", path.display(), e)))?;
+                scalar_map(value, path)?
+            }
+            _ => parse_dotenv(&content, path)?,
+        };
+        self.file_vars.extend(vars);
+        Ok(self)
+    }
+
+    /// Sets whether variables are looked up in the environment.
+    pub fn with_env(mut self, use_env: bool) -> Self {
+        self.use_env = use_env;
+        self
+    }
+
+    /// Sets whether placeholders without a value are an error.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    fn lookup(&self, name: &str) -> Option<String> {
+        self.vars
+            .get(name)
+            .or_else(|| self.file_vars.get(name))
+            .cloned()
+            .or_else(|| self.use_env.then(|| std::env::var(name).ok()).flatten())
+    }
+
+    /// Substitutes the placeholders of a string.
+    ///
+    /// # Returns
+    /// - The string with its placeholders substituted, or `Error::UndefinedVariables` in strict
+    ///   mode if a placeholder has no value.
+    pub fn render(&self, input: &str) -> Result<String> {
+        let mut undefined = Vec::new();
+        let output = self.render_into(input, &mut undefined);
+        self.check(undefined)?;
+        Ok(output)
+    }
+
+    /// Substitutes the placeholders of every string of a value, such as a `TesTask`.
+    ///
+    /// # Returns
+    /// - The value with its placeholders substituted, or `Error::UndefinedVariables` in strict
+    ///   mode, listing every placeholder without a value.
+    pub fn apply<T: Serialize + DeserializeOwned>(&self, value: &T) -> Result<T> {
+        let mut json = serde_json::to_value(value).map_err(|e| Error::InvalidInput(e.to_string()))?;
+        let mut undefined = Vec::new();
+        self.render_value(&mut json, &mut undefined);
+        self.check(undefined)?;
+        serde_json::from_value(json).map_err(|e| Error::InvalidInput(format!("invalid value after substitution: {}", e)))
+    }
+
+    fn check(&self, mut undefined: Vec<String>) -> Result<()> {
+        if !self.strict || undefined.is_empty() {
+            return Ok(());
+        }
+        let mut seen = std::collections::HashSet::new();
+        undefined.retain(|name| seen.insert(name.clone()));
+        Err(Error::UndefinedVariables(undefined))
+    }
+
+    fn render_value(&self, value: &mut Value, undefined: &mut Vec<String>) {
+        match value {
+            Value::String(s) => *s = self.render_into(s, undefined),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.render_value(item, undefined)),
+            Value::Object(map) => map.values_mut().for_each(|item| self.render_value(item, undefined)),
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+    }
+
+    fn render_into(&self, input: &str, undefined: &mut Vec<String>) -> String {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find("${") {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+            let placeholder = &after[..end];
+            let (name, default) = match placeholder.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (placeholder, None),
+            };
+            if !is_name(name) {
+                // not a placeholder, e.g. `${1}` or `${ NAME }`
+                output.push_str("${");
+                rest = after;
+                continue;
+            }
+            match (self.lookup(name), default) {
+                (Some(value), Some(default)) if value.is_empty() => output.push_str(default),
+                (Some(value), _) => output.push_str(&value),
+                (None, Some(default)) => output.push_str(default),
+                (None, None) => {
+                    undefined.push(name.to_string());
+                    output.push_str(&rest[start..start + 3 + end]);
+                }
+            }
+            rest = &after[end + 1..];
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn scalar_map(value: Value, path: &Path) -> Result<HashMap<String, String>> {
+    let Value::Object(map) = value else {
+        return Err(Error::InvalidInput(format!("variables file {} must hold a mapping", path.display())));
+    };
+    map.into_iter()
+        .map(|(key, value)| match value {
+            Value::String(s) => Ok((key, s)),
+            Value::Bool(_) | Value::Number(_) => Ok((key, value.to_string())),
+            Value::Null => Ok((key, String::new())),
+            Value::Array(_) | Value::Object(_) => Err(Error::InvalidInput(format!(
+                "variable `{}` in {} must be a scalar",
+                key,
+                path.display()
+            ))),
+        })
+        .collect()
+}
+
+fn parse_dotenv(content: &str, path: &Path) -> Result<HashMap<String, String>> {
+    let mut vars = HashMap::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line.split_once('=').ok_or_else(|| {
+            Error::InvalidInput(format!("{}:{}: expected KEY=VALUE", path.display(), number + 1))
+        })?;
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| value.strip_prefix(*quote).and_then(|v| v.strip_suffix(*quote)))
+            .unwrap_or(value);
+        vars.insert(key.trim().to_string(), value.to_string());
+    }
+    Ok(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::tes::models::TesTask;
+
+    fn vars() -> TemplateVars {
+        TemplateVars::new()
+            .with_env(false)
+            .with_var("NAME".to_string(), "world".to_string())
+            .with_var("EMPTY".to_string(), String::new())
+    }
+
+    #[test]
+    fn test_render() {
+        let vars = vars();
+        assert_eq!(vars.render("hello ${NAME}!").unwrap(), "hello world!");
+        assert_eq!(vars.render("${MISSING:-a:-b} ${EMPTY:-default} ${EMPTY}.").unwrap(), "a:-b default .");
+        assert_eq!(vars.render("$NAME ${1} ${NAME").unwrap(), "$NAME ${1} ${NAME");
+
+        match vars.render("${A} ${NAME} ${B} ${A}") {
+            Err(Error::UndefinedVariables(names)) => assert_eq!(names, vec!["A", "B"]),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let lenient = vars.with_strict(false);
+        assert_eq!(lenient.render("${A} ${NAME}").unwrap(), "${A} world");
+    }
+
+    #[test]
+    fn test_apply_to_task_with_files() {
+        let dir = std::env::temp_dir().join(format!("ga4gh-template-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let yaml = dir.join("vars.yaml");
+        std::fs::write(&yaml, "INPUT: s3://bucket/input.vcf.gz\nDISK: 200\n").unwrap();
+        let dotenv = dir.join("secrets.env");
+        std::fs::write(&dotenv, "# credentials\nexport AWS_REGION=\"eu-west-1\"\nINPUT=ignored\n").unwrap();
+
+        let task: TesTask = serde_json::from_str(
+            r#"{
+                "name": "disk ${DISK}",
+                "executors": [{
+                    "image": "amazon/aws-cli",
+                    "command": ["aws", "s3", "cp", "${INPUT}", "/vol/a/input.vcf.gz"],
+                    "env": {"AWS_REGION": "${AWS_REGION}", "AWS_PROFILE": "${AWS_PROFILE:-default}"}
+                }]
+            }"#,
+        )
+        .unwrap();
+        let vars = TemplateVars::new()
+            .with_env(false)
+            .with_file(&dotenv)
+            .unwrap()
+            .with_file(&yaml)
+            .unwrap();
+
+        let task = vars.apply(&task).unwrap();
+        assert_eq!(task.name.as_deref(), Some("disk 200"));
+        let executor = &task.executors.unwrap()[0];
+        assert_eq!(executor.command[3], "s3://bucket/input.vcf.gz");
+        let env = executor.env.as_ref().unwrap();
+        assert_eq!(env["AWS_REGION"], "eu-west-1");
+        assert_eq!(env["AWS_PROFILE"], "default");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
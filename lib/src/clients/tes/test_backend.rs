@@ -0,0 +1,100 @@
+use crate::clients::tes::wait::WaitOptions;
+use crate::clients::tes::Task;
+use crate::utils::backend::{HttpBackend, HttpRequest, HttpResponse, StatusCode};
+use crate::utils::configuration::Configuration;
+use crate::utils::transport::Transport;
+use crate::Result;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A TES returning the given documents of task `123` in turn, in `FULL` view, then the last
+/// one forever.
+pub(crate) struct SequenceTes {
+    documents: Vec<Value>,
+    /// The number of requests received so far.
+    pub(crate) polls: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl HttpBackend for SequenceTes {
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+        assert_eq!(request.url.query(), Some("view=FULL"));
+        let poll = self.polls.fetch_add(1, Ordering::SeqCst);
+        let body = &self.documents[poll.min(self.documents.len() - 1)];
+        Ok(HttpResponse::new(StatusCode::OK, body.to_string()))
+    }
+}
+
+/// Returns task `123` of a `SequenceTes` returning the given documents, with the backend.
+pub(crate) fn sequence_task(documents: Vec<Value>) -> (Task, Arc<SequenceTes>) {
+    let backend = Arc::new(SequenceTes {
+        documents,
+        polls: AtomicUsize::new(0),
+    });
+    let transport = Transport::with_backend(&test_configuration(), backend.clone()).unwrap();
+    (Task::new("123".to_string(), transport), backend)
+}
+
+/// A TES whose tasks move through the given documents, one per poll.
+pub(crate) struct ScriptedTes {
+    /// Whether the script advances on list requests, or on task requests otherwise.
+    listing: bool,
+    polls: AtomicUsize,
+    tasks: Vec<(&'static str, Vec<Value>)>,
+    /// The path and query of the requests received so far, relative to the tasks endpoint.
+    pub(crate) requests: Mutex<Vec<String>>,
+}
+
+impl ScriptedTes {
+    pub(crate) fn new(listing: bool, tasks: Vec<(&'static str, Vec<Value>)>) -> Arc<Self> {
+        Arc::new(ScriptedTes {
+            listing,
+            polls: AtomicUsize::new(0),
+            tasks,
+            requests: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn task(&self, id: &str, poll: usize) -> Value {
+        let (_, documents) = self.tasks.iter().find(|(task_id, _)| *task_id == id).unwrap();
+        documents[poll.min(documents.len() - 1)].clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpBackend for ScriptedTes {
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let path = request.url.path().trim_start_matches("/ga4gh/tes/v1/tasks").to_string();
+        self.requests.lock().unwrap().push(format!("{}?{}", path, request.url.query().unwrap_or_default()));
+        let body = if path.is_empty() {
+            let poll = self.polls.fetch_add(1, Ordering::SeqCst);
+            let state = request.url.query_pairs().find(|(key, _)| key == "state").map(|(_, v)| v.to_string());
+            let tasks: Vec<Value> = self
+                .tasks
+                .iter()
+                .map(|(id, _)| self.task(id, poll))
+                .filter(|task| state.is_none() || task["state"].as_str() == state.as_deref())
+                .collect();
+            json!({ "tasks": tasks })
+        } else {
+            let poll = match self.listing {
+                true => self.polls.load(Ordering::SeqCst).saturating_sub(1),
+                false => self.polls.fetch_add(1, Ordering::SeqCst),
+            };
+            self.task(path.trim_start_matches('/'), poll)
+        };
+        Ok(HttpResponse::new(StatusCode::OK, body.to_string()))
+    }
+}
+
+/// Returns the configuration of a TES that is never contacted, for test backends.
+pub(crate) fn test_configuration() -> Configuration {
+    Configuration::new(url::Url::parse("http://tes.invalid").unwrap())
+}
+
+/// Returns options polling every few milliseconds.
+pub(crate) fn fast() -> WaitOptions {
+    WaitOptions::default().with_interval(Duration::from_millis(5))
+}
@@ -0,0 +1,349 @@
+use crate::clients::tes::models::{TesExecutor, TesInput, TesOutput, TesResources, TesServiceInfo, TesTask};
+use std::fmt;
+
+/// A part of a task that violates the TES specification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The path of the offending field, e.g. `inputs[0].path`.
+    pub path: String,
+    /// What is wrong with the field.
+    pub message: String,
+}
+
+impl Violation {
+    fn new(path: String, message: &str) -> Self {
+        Violation {
+            path,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Formats violations as a `; `-separated list, for error messages.
+pub(crate) fn describe(violations: &[Violation]) -> String {
+    violations.iter().map(Violation::to_string).collect::<Vec<_>>().join("; ")
+}
+
+impl TesTask {
+    /// Checks the task against the TES specification before it is sent to a server.
+    ///
+    /// The checks cover what servers reject with an opaque `400 Bad Request`: a task without
+    /// executors, executors without an image or a command, relative container paths, inputs
+    /// with both or neither of `url` and `content`, outputs without a URL, output wildcards
+    /// without a `path_prefix`, and resources that are not positive.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the task is valid, or every violation found, with the path of its field.
+    pub fn validate(&self) -> std::result::Result<(), Vec<Violation>> {
+        let mut violations = Vec::new();
+
+        match self.executors.as_deref() {
+            None | Some([]) => violations.push(Violation::new(
+                "executors".to_string(),
+                "at least one executor is required",
+            )),
+            Some(executors) => {
+                for (i, executor) in executors.iter().enumerate() {
+                    validate_executor(&format!("executors[{}]", i), executor, &mut violations);
+                }
+            }
+        }
+        for (i, input) in self.inputs.iter().flatten().enumerate() {
+            validate_input(&format!("inputs[{}]", i), input, &mut violations);
+        }
+        for (i, output) in self.outputs.iter().flatten().enumerate() {
+            validate_output(&format!("outputs[{}]", i), output, &mut violations);
+        }
+        for (i, volume) in self.volumes.iter().flatten().enumerate() {
+            check_absolute(format!("volumes[{}]", i), volume, &mut violations);
+        }
+        if let Some(resources) = &self.resources {
+            validate_resources(resources, &mut violations);
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// The result of checking a task against the capabilities advertised by a TES service.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapabilityCheck {
+    /// What the service is known to reject.
+    pub errors: Vec<Violation>,
+    /// What the service may not support, as its service-info is not exhaustive.
+    pub warnings: Vec<Violation>,
+}
+
+impl TesServiceInfo {
+    /// Checks a task against the capabilities advertised in the service-info.
+    ///
+    /// An input or output URL whose scheme matches none of the `storage` locations is a
+    /// warning, as the service lists only some of the locations it supports. A backend
+    /// parameter missing from `tesResources_backend_parameters` is an error when the task sets
+    /// `backend_parameters_strict`, since the service then fails the task, and a warning
+    /// otherwise, since the service then ignores the parameter. If the service does not
+    /// advertise `tesResources_backend_parameters` at all, backend parameters are only warnings.
+    ///
+    /// # Arguments
+    /// - `task`: The task to check.
+    ///
+    /// # Returns
+    /// - The errors and warnings found, with the paths of their fields.
+    pub fn check_task(&self, task: &TesTask) -> CapabilityCheck {
+        let mut check = CapabilityCheck::default();
+
+        let storage: Vec<&str> = self
+            .storage
+            .iter()
+            .flatten()
+            .filter_map(|location| location.split_once("://").map(|(scheme, _)| scheme))
+            .collect();
+        if !storage.is_empty() {
+            let urls = task
+                .inputs
+                .iter()
+                .flatten()
+                .enumerate()
+                .filter_map(|(i, input)| Some((format!("inputs[{}].url", i), input.url.as_deref()?)))
+                .chain(
+                    task.outputs
+                        .iter()
+                        .flatten()
+                        .enumerate()
+                        .map(|(i, output)| (format!("outputs[{}].url", i), output.url.as_str())),
+                );
+            for (path, url) in urls {
+                let Some((scheme, _)) = url.split_once("://") else { continue };
+                if !storage.iter().any(|supported| supported.eq_ignore_ascii_case(scheme)) {
+                    check.warnings.push(Violation {
+                        path,
+                        message: format!(
+                            "`{}` is not among the storage of the service ({})",
+                            scheme,
+                            storage.join(", ")
+                        ),
+                    });
+                }
+            }
+        }
+
+        if let Some(resources) = &task.resources {
+            let advertised = self.tes_resources_backend_parameters.as_deref();
+            let supported = advertised.unwrap_or_default();
+            let strict = resources.backend_parameters_strict.unwrap_or(false);
+            let mut keys: Vec<&String> = resources.backend_parameters.iter().flatten().map(|(key, _)| key).collect();
+            keys.sort();
+            for key in keys.into_iter().filter(|key| !supported.contains(key)) {
+                let violation = Violation {
+                    path: format!("resources.backend_parameters.{}", key),
+                    message: match advertised {
+                        None => "the service does not list the backend parameters it supports".to_string(),
+                        Some([]) => "the service supports no backend parameters".to_string(),
+                        Some(_) => format!("not supported by the service, which supports {}", supported.join(", ")),
+                    },
+                };
+                if strict && advertised.is_some() {
+                    check.errors.push(violation);
+                } else {
+                    check.warnings.push(violation);
+                }
+            }
+        }
+
+        check
+    }
+}
+
+fn validate_executor(path: &str, executor: &TesExecutor, violations: &mut Vec<Violation>) {
+    if executor.image.trim().is_empty() {
+        violations.push(Violation::new(format!("{}.image", path), "an image is required"));
+    }
+    if executor.command.is_empty() {
+        violations.push(Violation::new(format!("{}.command", path), "a command is required"));
+    }
+    let paths = [
+        ("workdir", &executor.workdir),
+        ("stdin", &executor.stdin),
+        ("stdout", &executor.stdout),
+        ("stderr", &executor.stderr),
+    ];
+    for (field, value) in paths {
+        if let Some(value) = value {
+            check_absolute(format!("{}.{}", path, field), value, violations);
+        }
+    }
+}
+
+fn validate_input(path: &str, input: &TesInput, violations: &mut Vec<Violation>) {
+    check_absolute(format!("{}.path", path), &input.path, violations);
+    match (&input.url, &input.content) {
+        (Some(_), Some(_)) => violations.push(Violation::new(
+            format!("{}.content", path),
+            "`url` and `content` are mutually exclusive",
+        )),
+        (None, None) => violations.push(Violation::new(
+            format!("{}.url", path),
+            "either `url` or `content` is required",
+        )),
+        (Some(url), None) => check_url(format!("{}.url", path), url, violations),
+        (None, Some(_)) => {}
+    }
+}
+
+fn validate_output(path: &str, output: &TesOutput, violations: &mut Vec<Violation>) {
+    check_url(format!("{}.url", path), &output.url, violations);
+    check_absolute(format!("{}.path", path), &output.path, violations);
+    if output.path.contains(['*', '?', '[']) && output.path_prefix.is_none() {
+        violations.push(Violation::new(
+            format!("{}.path_prefix", path),
+            "a path prefix is required for paths with wildcards",
+        ));
+    }
+}
+
+fn validate_resources(resources: &TesResources, violations: &mut Vec<Violation>) {
+    if resources.cpu_cores.is_some_and(|cpu_cores| cpu_cores <= 0) {
+        violations.push(Violation::new("resources.cpu_cores".to_string(), "must be positive"));
+    }
+    if resources.ram_gb.is_some_and(|ram_gb| ram_gb <= 0.0) {
+        violations.push(Violation::new("resources.ram_gb".to_string(), "must be positive"));
+    }
+    if resources.disk_gb.is_some_and(|disk_gb| disk_gb <= 0.0) {
+        violations.push(Violation::new("resources.disk_gb".to_string(), "must be positive"));
+    }
+}
+
+fn check_absolute(path: String, value: &str, violations: &mut Vec<Violation>) {
+    if !value.starts_with('/') {
+        violations.push(Violation::new(path, "must be an absolute path"));
+    }
+}
+
+fn check_url(path: String, value: &str, violations: &mut Vec<Violation>) {
+    if value.trim().is_empty() {
+        violations.push(Violation::new(path, "a URL is required"));
+    } else if url::Url::parse(value).is_err() {
+        violations.push(Violation::new(path, "must be an absolute URL"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::tes::models::TesResources;
+
+    fn paths(task: &TesTask) -> Vec<String> {
+        task.validate().unwrap_err().into_iter().map(|violation| violation.path).collect()
+    }
+
+    #[test]
+    fn test_validate_reports_every_violation() {
+        assert_eq!(paths(&TesTask::default()), vec!["executors"]);
+
+        let task = TesTask {
+            executors: Some(vec![TesExecutor {
+                workdir: Some("data".to_string()),
+                ..TesExecutor::new(String::new(), vec![])
+            }]),
+            inputs: Some(vec![
+                TesInput {
+                    url: Some("s3://bucket/a".to_string()),
+                    content: Some("a".to_string()),
+                    ..TesInput::new("/data/a".to_string())
+                },
+                TesInput::new("data/b".to_string()),
+            ]),
+            outputs: Some(vec![
+                TesOutput::new(String::new(), "/data/out".to_string()),
+                TesOutput::new("s3://bucket/out/".to_string(), "/data/*.txt".to_string()),
+            ]),
+            resources: Some(Box::new(TesResources {
+                cpu_cores: Some(0),
+                ..TesResources::new()
+            })),
+            ..TesTask::default()
+        };
+        assert_eq!(
+            paths(&task),
+            vec![
+                "executors[0].image",
+                "executors[0].command",
+                "executors[0].workdir",
+                "inputs[0].content",
+                "inputs[1].path",
+                "inputs[1].url",
+                "outputs[0].url",
+                "outputs[1].path_prefix",
+                "resources.cpu_cores",
+            ]
+        );
+        assert_eq!(
+            task.validate().unwrap_err()[3].to_string(),
+            "inputs[0].content: `url` and `content` are mutually exclusive"
+        );
+    }
+
+    #[test]
+    fn test_check_task_capabilities() {
+        let service_info = TesServiceInfo {
+            storage: Some(vec!["s3://bucket/storage".to_string(), "file:///data".to_string()]),
+            tes_resources_backend_parameters: Some(vec!["VmSize".to_string()]),
+            ..TesServiceInfo::default()
+        };
+        let mut task = TesTask {
+            inputs: Some(vec![
+                TesInput {
+                    url: Some("S3://bucket/a".to_string()),
+                    ..TesInput::new("/data/a".to_string())
+                },
+                TesInput {
+                    content: Some("b".to_string()),
+                    ..TesInput::new("/data/b".to_string())
+                },
+            ]),
+            outputs: Some(vec![TesOutput::new("gs://bucket/out".to_string(), "/data/out".to_string())]),
+            resources: Some(Box::new(TesResources {
+                backend_parameters: Some(
+                    [("VmSize", "large"), ("Queue", "fast")]
+                        .into_iter()
+                        .map(|(key, value)| (key.to_string(), value.to_string()))
+                        .collect(),
+                ),
+                ..TesResources::new()
+            })),
+            ..TesTask::default()
+        };
+
+        let check = service_info.check_task(&task);
+        assert!(check.errors.is_empty());
+        let warnings: Vec<&str> = check.warnings.iter().map(|warning| warning.path.as_str()).collect();
+        assert_eq!(warnings, vec!["outputs[0].url", "resources.backend_parameters.Queue"]);
+
+        task.resources.as_mut().unwrap().backend_parameters_strict = Some(true);
+        let check = service_info.check_task(&task);
+        assert_eq!(
+            check.errors[0].to_string(),
+            "resources.backend_parameters.Queue: not supported by the service, which supports VmSize"
+        );
+
+        // a service that does not advertise its backend parameters may still support them
+        let service_info = TesServiceInfo::default();
+        let check = service_info.check_task(&task);
+        assert!(check.errors.is_empty());
+        let warnings: Vec<&str> = check.warnings.iter().map(|warning| warning.path.as_str()).collect();
+        assert_eq!(
+            warnings,
+            vec!["resources.backend_parameters.Queue", "resources.backend_parameters.VmSize"]
+        );
+    }
+}
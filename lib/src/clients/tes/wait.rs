@@ -0,0 +1,361 @@
+use crate::clients::tes::models::{TesState, TesTask, TesView};
+use crate::clients::tes::Task;
+use crate::utils::telemetry::{operation_span, traced};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use futures::stream::{self, Stream};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::time::Instant;
+pub use tokio_util::sync::CancellationToken;
+
+impl TesState {
+    /// Returns whether the task has finished and its state will not change anymore:
+    /// `COMPLETE`, `EXECUTOR_ERROR`, `SYSTEM_ERROR`, `CANCELED` or `PREEMPTED`.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Self::Complete | Self::ExecutorError | Self::SystemError | Self::Canceled | Self::Preempted
+        )
+    }
+
+    /// Returns whether the task has finished successfully, i.e. is `COMPLETE`.
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Complete)
+    }
+
+    /// Returns whether the task is still being processed: `QUEUED`, `INITIALIZING`,
+    /// `RUNNING`, `PAUSED` or `CANCELING`. `UNKNOWN` is neither active nor terminal.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            Self::Queued | Self::Initializing | Self::Running | Self::Paused | Self::Canceling
+        )
+    }
+}
+
+/// How `Task::wait` polls a task until it finishes.
+///
+/// The first poll happens right away. The delay between two polls starts at `interval` and is
+/// multiplied by `backoff` after each poll, up to `max_interval`. Without a `timeout`, the wait
+/// only ends when the task finishes, a request fails or the cancellation token is cancelled.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::clients::tes::wait::{CancellationToken, WaitOptions};
+/// use std::time::Duration;
+///
+/// let token = CancellationToken::new();
+/// let options = WaitOptions::default()
+///     .with_interval(Duration::from_secs(2))
+///     .with_backoff(2.0, Duration::from_secs(60))
+///     .with_timeout(Duration::from_secs(3600))
+///     .with_cancellation_token(token.clone());
+/// ```
+#[derive(Debug, Clone)]
+pub struct WaitOptions {
+    /// The delay after the first poll.
+    pub interval: Duration,
+    /// The factor the delay is multiplied by after each poll; `1.0` polls at a fixed interval.
+    pub backoff: f64,
+    /// The upper bound of the delay between two polls.
+    pub max_interval: Duration,
+    /// The overall time after which the wait fails with `Error::WaitTimeout`.
+    pub timeout: Option<Duration>,
+    /// A token that makes the wait fail with `Error::Cancelled` when cancelled.
+    pub cancellation_token: Option<CancellationToken>,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        WaitOptions {
+            interval: Duration::from_secs(1),
+            backoff: 1.5,
+            max_interval: Duration::from_secs(30),
+            timeout: None,
+            cancellation_token: None,
+        }
+    }
+}
+
+impl WaitOptions {
+    /// Sets the delay after the first poll.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the factor the delay grows by after each poll and the upper bound of the delay.
+    pub fn with_backoff(mut self, backoff: f64, max_interval: Duration) -> Self {
+        self.backoff = backoff;
+        self.max_interval = max_interval;
+        self
+    }
+
+    /// Sets the overall time after which the wait fails.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a token to cancel the wait with.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Returns the delay following the given one.
+    pub(crate) fn next_interval(&self, interval: Duration) -> Duration {
+        interval.mul_f64(self.backoff.max(1.0)).min(self.max_interval)
+    }
+}
+
+/// What a `Poller` follows: each poll fetches an observation, which may queue items.
+#[async_trait]
+pub(crate) trait Observer: Send + Sync {
+    /// What a poll fetches.
+    type Observation: Send;
+    /// What the stream of the poller yields.
+    type Item: Send;
+
+    /// Fetches the next observation.
+    async fn poll(&self) -> Result<Self::Observation>;
+
+    /// Queues the items of an observation, returning whether the poller is done.
+    fn observe(&mut self, observation: Self::Observation, items: &mut VecDeque<Self::Item>) -> bool;
+
+    /// Returns the error ending a poller that timed out.
+    fn timeout_error(&self) -> Error;
+}
+
+/// Polls an `Observer` with the intervals, timeout and cancellation token of `WaitOptions`.
+///
+/// The first poll happens right away. The interval goes back to its initial value after a poll
+/// that queued items. The stream of the poller ends once the observer is done, or after
+/// yielding the error of a failed poll, `Error::WaitTimeout` or `Error::Cancelled`.
+pub(crate) struct Poller<O: Observer> {
+    observer: O,
+    options: WaitOptions,
+    token: CancellationToken,
+    deadline: Option<Instant>,
+    interval: Duration,
+    items: VecDeque<O::Item>,
+    polled: bool,
+    done: bool,
+}
+
+impl<O: Observer> Poller<O> {
+    pub(crate) fn new(observer: O, options: WaitOptions) -> Self {
+        Poller {
+            observer,
+            token: options.cancellation_token.clone().unwrap_or_default(),
+            deadline: options.timeout.map(|timeout| Instant::now() + timeout),
+            interval: options.interval,
+            options,
+            items: VecDeque::new(),
+            polled: false,
+            done: false,
+        }
+    }
+
+    /// Returns the next item of the stream, or `None` when the poller is done.
+    async fn next(&mut self) -> Option<Result<O::Item>> {
+        loop {
+            if let Some(item) = self.items.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.done {
+                return None;
+            }
+            let deadline = self.deadline;
+            let timed_out = async {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                }
+            };
+            let wait = if self.polled { self.interval } else { Duration::ZERO };
+            let polled = tokio::select! {
+                polled = async {
+                    tokio::time::sleep(wait).await;
+                    self.observer.poll().await
+                } => Some(polled),
+                _ = timed_out => None,
+                _ = self.token.cancelled() => {
+                    self.done = true;
+                    return Some(Err(Error::Cancelled));
+                }
+            };
+            let Some(polled) = polled else {
+                self.done = true;
+                return Some(Err(self.observer.timeout_error()));
+            };
+            let first = !self.polled;
+            self.polled = true;
+
+            match polled {
+                Ok(observation) => {
+                    let queued = self.items.len();
+                    self.done = self.observer.observe(observation, &mut self.items);
+                    // poll quickly again while things are changing
+                    self.interval = if first || self.items.len() > queued {
+                        self.options.interval
+                    } else {
+                        self.options.next_interval(self.interval)
+                    };
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+
+    pub(crate) fn into_stream(self) -> impl Stream<Item = Result<O::Item>> {
+        stream::unfold(self, |mut poller| async move {
+            let item = poller.next().await?;
+            Some((item, poller))
+        })
+    }
+}
+
+/// Follows a task until it reaches a terminal state, queueing the finished task.
+struct Wait {
+    task: Task,
+    last_state: TesState,
+}
+
+#[async_trait]
+impl Observer for Wait {
+    type Observation = TesTask;
+    type Item = TesTask;
+
+    async fn poll(&self) -> Result<TesTask> {
+        self.task.get(TesView::Full).await
+    }
+
+    fn observe(&mut self, task: TesTask, items: &mut VecDeque<TesTask>) -> bool {
+        self.last_state = task.state.unwrap_or_default();
+        let finished = self.last_state.is_terminal();
+        if finished {
+            items.push_back(task);
+        }
+        finished
+    }
+
+    fn timeout_error(&self) -> Error {
+        Error::WaitTimeout {
+            task_id: self.task.id.clone(),
+            last_state: self.last_state,
+        }
+    }
+}
+
+impl Task {
+    /// Polls the task until it reaches a terminal state.
+    ///
+    /// A missing or `UNKNOWN` state is treated like an active one, so polling goes on.
+    ///
+    /// # Arguments
+    /// - `options`: The polling intervals, timeout and cancellation token.
+    ///
+    /// # Returns
+    /// - On success, returns the finished task in `FULL` view, whether it succeeded or not;
+    ///   check its state with `TesState::is_success`.
+    /// - `Error::WaitTimeout` if the timeout elapses first, `Error::Cancelled` if the token is
+    ///   cancelled first, or the error of a failed request.
+    pub async fn wait(&self, options: WaitOptions) -> Result<TesTask> {
+        let span = operation_span("tes", "wait_task");
+        span.record("task_id", self.id.as_str());
+        let wait = Wait {
+            task: self.clone(),
+            last_state: TesState::Unknown,
+        };
+        traced(span, async {
+            let mut poller = Poller::new(wait, options);
+            poller.next().await.expect("a poller yields an item or an error before it is done")
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::tes::test_backend::{fast, sequence_task, SequenceTes};
+    use std::sync::Arc;
+    use serde_json::{json, Value};
+    use std::sync::atomic::Ordering;
+
+    fn document(state: Option<&str>) -> Value {
+        match state {
+            Some(state) => json!({"id": "123", "state": state, "executors": []}),
+            None => json!({"id": "123", "executors": []}),
+        }
+    }
+
+    fn task(states: Vec<Option<&str>>) -> (Task, Arc<SequenceTes>) {
+        sequence_task(states.into_iter().map(document).collect())
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_terminal_state() {
+        let (task, backend) = task(vec![None, Some("UNKNOWN"), Some("QUEUED"), Some("RUNNING"), Some("EXECUTOR_ERROR")]);
+
+        let finished = task.wait(fast()).await.expect("Failed to wait for task");
+        assert_eq!(finished.state, Some(TesState::ExecutorError));
+        assert!(!finished.state.unwrap().is_success());
+        assert_eq!(backend.polls.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_wait_first_delay_is_interval() {
+        let (task, _) = task(vec![Some("RUNNING"), Some("COMPLETE")]);
+        let options = WaitOptions::default()
+            .with_interval(Duration::from_millis(50))
+            .with_backoff(10.0, Duration::from_secs(5));
+
+        let started = std::time::Instant::now();
+        task.wait(options).await.expect("Failed to wait for task");
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(started.elapsed() < Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_wait_timeout() {
+        let (task, _) = task(vec![Some("RUNNING")]);
+
+        let result = task.wait(fast().with_timeout(Duration::from_millis(100))).await;
+        match result {
+            Err(Error::WaitTimeout { task_id, last_state }) => {
+                assert_eq!(task_id, "123");
+                assert_eq!(last_state, TesState::Running);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_cancelled() {
+        let (task, _) = task(vec![Some("QUEUED")]);
+        let token = CancellationToken::new();
+
+        let cancel = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            cancel.cancel();
+        });
+        let result = task.wait(fast().with_cancellation_token(token)).await;
+        assert!(matches!(result, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_state_helpers() {
+        assert!(TesState::Complete.is_terminal() && TesState::Complete.is_success());
+        assert!(TesState::Preempted.is_terminal() && !TesState::Preempted.is_success());
+        assert!(TesState::Canceling.is_active() && !TesState::Canceling.is_terminal());
+        assert!(!TesState::Unknown.is_active() && !TesState::Unknown.is_terminal());
+    }
+}
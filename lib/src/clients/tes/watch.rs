@@ -0,0 +1,309 @@
+use crate::clients::tes::models::{ListTasksParams, TesExecutorLog, TesState, TesTask, TesView};
+use crate::clients::tes::wait::{Observer, Poller, WaitOptions};
+use crate::clients::tes::{Task, TES};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use futures::stream::{Stream, TryStreamExt};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::SystemTime;
+
+/// A change observed on a watched task.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskEvent {
+    /// The ID of the task.
+    pub task_id: String,
+    /// The state before the change, `None` on the first observation of the task.
+    pub old_state: Option<TesState>,
+    /// The state after the change. It equals `old_state` when only new executor logs appeared.
+    pub new_state: TesState,
+    /// The time the change was observed.
+    pub timestamp: SystemTime,
+    /// The executor logs that appeared since the previous event, in `BASIC` view, i.e.
+    /// without `stdout` and `stderr`.
+    pub executor_logs: Vec<TesExecutorLog>,
+}
+
+/// What is known about a watched task.
+struct Observed {
+    state: TesState,
+    executor_logs: usize,
+}
+
+/// The tasks a watch polls.
+enum Source<'a> {
+    Task(Box<Task>),
+    Tasks { tes: &'a TES, filter: ListTasksParams },
+}
+
+impl Source<'_> {
+    /// Fetches the watched tasks in `BASIC` view.
+    ///
+    /// Tasks are listed in batches when possible. Tasks still being watched that no longer match
+    /// the filter, e.g. because they left the filtered state, are fetched one by one.
+    async fn poll(&self, tracked: &HashMap<String, Observed>) -> Result<Vec<TesTask>> {
+        match self {
+            Source::Task(task) => Ok(vec![task.get(TesView::Basic).await?]),
+            Source::Tasks { tes, filter } => {
+                let params = ListTasksParams {
+                    view: Some(TesView::Basic),
+                    page_token: None,
+                    ..filter.clone()
+                };
+                let mut tasks: Vec<TesTask> = tes.list_all_tasks(Some(params), None).try_collect().await?;
+                let listed: HashSet<String> = tasks.iter().filter_map(|task| task.id.clone()).collect();
+                for (id, observed) in tracked {
+                    if !observed.state.is_terminal() && !listed.contains(id) {
+                        let task = Task::new(id.clone(), tes.transport.clone());
+                        tasks.push(task.get(TesView::Basic).await?);
+                    }
+                }
+                Ok(tasks)
+            }
+        }
+    }
+}
+
+/// The tasks of a watch and what is known about them.
+struct Watch<'a> {
+    source: Source<'a>,
+    tracked: HashMap<String, Observed>,
+}
+
+impl<'a> Watch<'a> {
+    fn new(source: Source<'a>) -> Self {
+        Watch {
+            source,
+            tracked: HashMap::new(),
+        }
+    }
+
+    /// Records the task and queues an event if its state changed or executor logs appeared.
+    fn observe_task(&mut self, task: TesTask, events: &mut VecDeque<TaskEvent>) {
+        let Some(task_id) = task.id else { return };
+        let state = task.state.unwrap_or_default();
+        let executor_logs: Vec<TesExecutorLog> =
+            task.logs.into_iter().flatten().flat_map(|log| log.logs).collect();
+
+        let (old_state, seen) = match self.tracked.get(&task_id) {
+            Some(observed) => (Some(observed.state), observed.executor_logs),
+            None => (None, 0),
+        };
+        if old_state == Some(state) && executor_logs.len() <= seen {
+            return;
+        }
+        self.tracked.insert(
+            task_id.clone(),
+            Observed {
+                state,
+                executor_logs: executor_logs.len().max(seen),
+            },
+        );
+        events.push_back(TaskEvent {
+            task_id,
+            old_state,
+            new_state: state,
+            timestamp: SystemTime::now(),
+            executor_logs: executor_logs.into_iter().skip(seen).collect(),
+        });
+    }
+}
+
+#[async_trait]
+impl Observer for Watch<'_> {
+    type Observation = Vec<TesTask>;
+    type Item = TaskEvent;
+
+    async fn poll(&self) -> Result<Vec<TesTask>> {
+        self.source.poll(&self.tracked).await
+    }
+
+    fn observe(&mut self, tasks: Vec<TesTask>, events: &mut VecDeque<TaskEvent>) -> bool {
+        for task in tasks {
+            self.observe_task(task, events);
+        }
+        self.tracked.values().all(|observed| observed.state.is_terminal())
+    }
+
+    fn timeout_error(&self) -> Error {
+        let (task_id, observed) = self
+            .tracked
+            .iter()
+            .find(|(_, observed)| !observed.state.is_terminal())
+            .or_else(|| self.tracked.iter().next())
+            .map(|(id, observed)| (id.clone(), Some(observed.state)))
+            .unwrap_or_default();
+        Error::WaitTimeout {
+            task_id,
+            last_state: observed.unwrap_or_default(),
+        }
+    }
+}
+
+impl Task {
+    /// Watches the task, polling it in `BASIC` view.
+    ///
+    /// The stream yields an event for the first observation of the task, then one for each
+    /// state change or batch of new executor logs. It ends after the event of a terminal state,
+    /// or after yielding the error of a failed request, `Error::WaitTimeout` or, when the
+    /// cancellation token is cancelled, `Error::Cancelled`.
+    ///
+    /// # Arguments
+    /// - `options`: The polling intervals, timeout and cancellation token. The interval goes
+    ///   back to its initial value after each event.
+    ///
+    /// # Returns
+    /// - A stream of `TaskEvent`s.
+    pub fn watch(&self, options: WaitOptions) -> impl Stream<Item = Result<TaskEvent>> + 'static {
+        Poller::new(Watch::new(Source::Task(Box::new(self.clone()))), options).into_stream()
+    }
+}
+
+impl TES {
+    /// Watches the tasks matching a filter, listing them in `BASIC` view with as few requests
+    /// as possible.
+    ///
+    /// The tasks matching the filter on the first poll are watched, and tasks matching it later
+    /// on are added. The stream yields the same events as `Task::watch` for each of them and
+    /// ends once all the watched tasks are terminal, including when no task matches at all.
+    ///
+    /// # Arguments
+    /// - `filter`: The filter of the tasks to watch; its `view` and `page_token` are ignored.
+    /// - `options`: The polling intervals, timeout and cancellation token.
+    ///
+    /// # Returns
+    /// - A stream of `TaskEvent`s.
+    pub fn watch_tasks(
+        &self,
+        filter: ListTasksParams,
+        options: WaitOptions,
+    ) -> impl Stream<Item = Result<TaskEvent>> + '_ {
+        Poller::new(Watch::new(Source::Tasks { tes: self, filter }), options).into_stream()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::tes::test_backend::{fast, test_configuration, ScriptedTes};
+    use crate::utils::configuration::Configuration;
+    use crate::utils::transport::Transport;
+    use futures::StreamExt;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio_util::sync::CancellationToken;
+
+    fn document(id: &str, state: &str, exit_codes: &[i32]) -> Value {
+        let logs: Vec<Value> = exit_codes.iter().map(|code| json!({"exit_code": code})).collect();
+        json!({"id": id, "state": state, "logs": [{"logs": logs, "outputs": []}]})
+    }
+
+    fn transport(backend: Arc<ScriptedTes>) -> Transport {
+        Transport::with_backend(&test_configuration(), backend).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_task_watch() {
+        let backend = ScriptedTes::new(false, vec![(
+            "1",
+            vec![
+                document("1", "QUEUED", &[]),
+                document("1", "RUNNING", &[]),
+                document("1", "RUNNING", &[0]),
+                document("1", "COMPLETE", &[0, 1]),
+            ],
+        )]);
+        let task = Task::new("1".to_string(), transport(backend.clone()));
+        let events: Vec<TaskEvent> = task
+            .watch(fast())
+            .map(|event| event.expect("Failed to watch task"))
+            .collect()
+            .await;
+
+        let states: Vec<(Option<TesState>, TesState, usize)> = events
+            .iter()
+            .map(|event| (event.old_state, event.new_state, event.executor_logs.len()))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (None, TesState::Queued, 0),
+                (Some(TesState::Queued), TesState::Running, 0),
+                (Some(TesState::Running), TesState::Running, 1),
+                (Some(TesState::Running), TesState::Complete, 1),
+            ]
+        );
+        assert_eq!(events[3].executor_logs[0].exit_code, 1);
+        assert!(backend.requests.lock().unwrap().iter().all(|request| request == "/1?view=BASIC"));
+    }
+
+    #[tokio::test]
+    async fn test_watch_tasks() {
+        let backend = ScriptedTes::new(true, vec![
+            ("1", vec![document("1", "RUNNING", &[]), document("1", "COMPLETE", &[0])]),
+            (
+                "2",
+                vec![
+                    document("2", "RUNNING", &[]),
+                    document("2", "RUNNING", &[]),
+                    document("2", "SYSTEM_ERROR", &[]),
+                ],
+            ),
+        ]);
+        let tes = TES::for_test(Configuration::default(), transport(backend.clone()));
+        let filter = ListTasksParams {
+            state: Some(TesState::Running),
+            ..Default::default()
+        };
+
+        let events: Vec<TaskEvent> = tes
+            .watch_tasks(filter, fast())
+            .map(|event| event.expect("Failed to watch tasks"))
+            .collect()
+            .await;
+
+        let mut changes: Vec<(String, Option<TesState>, TesState)> = events
+            .into_iter()
+            .map(|event| (event.task_id, event.old_state, event.new_state))
+            .collect();
+        changes.sort_by_key(|(id, old_state, _)| (id.clone(), old_state.is_some()));
+        assert_eq!(
+            changes,
+            vec![
+                ("1".to_string(), None, TesState::Running),
+                ("1".to_string(), Some(TesState::Running), TesState::Complete),
+                ("2".to_string(), None, TesState::Running),
+                ("2".to_string(), Some(TesState::Running), TesState::SystemError),
+            ]
+        );
+
+        // tasks leaving the filtered state are fetched one by one
+        let requests = backend.requests.lock().unwrap();
+        assert!(requests.contains(&"?state=RUNNING&view=BASIC".to_string()));
+        assert!(requests.contains(&"/1?view=BASIC".to_string()));
+        assert!(requests.contains(&"/2?view=BASIC".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_watch_timeout_and_cancel() {
+        let backend = ScriptedTes::new(false, vec![("1", vec![document("1", "QUEUED", &[])])]);
+        let task = Task::new("1".to_string(), transport(backend));
+
+        let items: Vec<Result<TaskEvent>> = task
+            .watch(fast().with_timeout(Duration::from_millis(50)))
+            .collect()
+            .await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(
+            &items[1],
+            Err(Error::WaitTimeout { task_id, last_state: TesState::Queued }) if task_id == "1"
+        ));
+
+        let token = CancellationToken::new();
+        let mut stream = Box::pin(task.watch(fast().with_cancellation_token(token.clone())));
+        assert!(stream.next().await.unwrap().is_ok());
+        token.cancel();
+        assert!(matches!(stream.next().await, Some(Err(Error::Cancelled))));
+        assert!(stream.next().await.is_none());
+    }
+}
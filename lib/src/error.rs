@@ -0,0 +1,256 @@
+use crate::clients::tes::models::TesState;
+use crate::clients::tes::validation::{describe, Violation};
+use crate::clients::trs::models::Error as TrsError;
+use crate::clients::wes::models::WesErrorResponse;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+
+/// A specialized `Result` type for SDK operations.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The error type returned by all clients of the SDK.
+///
+/// Every failure is mapped to one of the variants below, so callers can tell an HTTP error
+/// response (e.g. a 404 or a 401) from a network failure, a timeout or a malformed response
+/// without matching on strings.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::{Error, ErrorBody};
+///
+/// fn describe(error: &Error) -> String {
+///     match error {
+///         Error::Http { status, body: ErrorBody::Wes(wes_error), .. } => {
+///             format!("WES error {}: {:?}", status, wes_error.msg)
+///         }
+///         Error::Http { status, .. } if status.as_u16() == 404 => "not found".to_string(),
+///         Error::Timeout => "timed out".to_string(),
+///         other => other.to_string(),
+///     }
+/// }
+/// ```
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The server answered with a non-success HTTP status.
+    #[error("request failed with status {status}: {body}")]
+    Http {
+        /// The HTTP status code of the response.
+        status: StatusCode,
+        /// The headers of the response.
+        headers: Box<HeaderMap>,
+        /// The parsed response body.
+        body: ErrorBody,
+    },
+    /// The request could not be sent, or the response could not be read.
+    #[error("HTTP transport error: {0}")]
+    Transport(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The request did not complete in time.
+    #[error("request timed out")]
+    Timeout,
+    /// The request was not sent because the circuit breaker of the service is open after
+    /// repeated failures.
+    #[error("circuit breaker open for {base_url}, retry in {retry_after:?}")]
+    CircuitOpen {
+        /// The base URL of the failing service.
+        base_url: String,
+        /// The time left until the breaker lets a trial request through.
+        retry_after: std::time::Duration,
+    },
+    /// A task did not reach a terminal state before the wait timed out.
+    #[error("task {task_id} did not finish in time, last state: {last_state}")]
+    WaitTimeout {
+        /// The ID of the task.
+        task_id: String,
+        /// The last state reported by the server.
+        last_state: TesState,
+    },
+    /// The operation was cancelled by the caller.
+    #[error("operation cancelled")]
+    Cancelled,
+    /// The response body could not be deserialized into the expected model.
+    #[error("failed to deserialize response at `{path}`: {source}")]
+    Deserialization {
+        /// The JSON path at which deserialization failed, e.g. `tasks[0].state`.
+        path: String,
+        /// The underlying JSON error.
+        #[source]
+        source: serde_json::Error,
+        /// The raw response body.
+        body: String,
+    },
+    /// The SDK or the service it points to is misconfigured.
+    #[error("configuration error: {0}")]
+    Configuration(String),
+    /// The caller passed a value that cannot be sent in a request.
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    /// A request was not sent because it violates the specification of the API.
+    #[error("invalid request: {}", describe(.0))]
+    Validation(Vec<Violation>),
+    /// Template placeholders had no value; holds the names of their variables.
+    #[error("undefined template variables: {}", .0.join(", "))]
+    UndefinedVariables(Vec<String>),
+    /// A local I/O operation, such as reading a file, failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The body of an HTTP error response.
+///
+/// The body is parsed into the error model of the GA4GH API that returned it when its shape
+/// matches one, and kept as generic JSON or text otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorBody {
+    /// A WES `ErrorResponse` (`msg` and/or `status_code`).
+    Wes(WesErrorResponse),
+    /// A TRS `Error` (`code` and optional `message`).
+    Trs(TrsError),
+    /// Any other JSON body.
+    Json(Value),
+    /// A body that is not JSON.
+    Text(String),
+    /// An empty body.
+    Empty,
+}
+
+impl ErrorBody {
+    /// Parses the body of an error response.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The raw response body.
+    ///
+    /// # Returns
+    ///
+    /// The most specific `ErrorBody` matching the content.
+    pub fn parse(content: &str) -> Self {
+        if content.trim().is_empty() {
+            return ErrorBody::Empty;
+        }
+        let value = match serde_json::from_str::<Value>(content) {
+            Ok(value) => value,
+            Err(_) => return ErrorBody::Text(content.to_string()),
+        };
+        if let Some(object) = value.as_object() {
+            if object.get("code").is_some_and(Value::is_i64) {
+                if let Ok(trs_error) = serde_json::from_value::<TrsError>(value.clone()) {
+                    return ErrorBody::Trs(trs_error);
+                }
+            }
+            if object.contains_key("msg") || object.contains_key("status_code") {
+                if let Ok(wes_error) = serde_json::from_value::<WesErrorResponse>(value.clone()) {
+                    return ErrorBody::Wes(wes_error);
+                }
+            }
+        }
+        ErrorBody::Json(value)
+    }
+}
+
+impl fmt::Display for ErrorBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorBody::Wes(wes_error) => write!(f, "{}", wes_error.msg.as_deref().unwrap_or("no message")),
+            ErrorBody::Trs(trs_error) => write!(f, "{}", trs_error.message.as_deref().unwrap_or("no message")),
+            ErrorBody::Json(value) => write!(f, "{}", value),
+            ErrorBody::Text(text) => write!(f, "{}", text),
+            ErrorBody::Empty => write!(f, "empty response"),
+        }
+    }
+}
+
+impl Error {
+    /// Returns the HTTP status code if the error is an HTTP error response.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::Http { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Error::Timeout
+        } else {
+            Error::Transport(Box::new(e))
+        }
+    }
+}
+
+impl From<tokio::time::error::Elapsed> for Error {
+    fn from(_: tokio::time::error::Elapsed) -> Self {
+        Error::Timeout
+    }
+}
+
+/// Deserializes a JSON response body, recording the path at which deserialization failed.
+///
+/// # Arguments
+///
+/// * `body` - The raw response body.
+///
+/// # Returns
+///
+/// The deserialized value, or an `Error::Deserialization` holding the JSON path of the failure.
+pub(crate) fn from_json<T: DeserializeOwned>(body: &str) -> Result<T> {
+    let deserializer = &mut serde_json::Deserializer::from_str(body);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        Error::Deserialization {
+            path,
+            source: e.into_inner(),
+            body: body.to_string(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::tes::models::TesTask;
+
+    #[test]
+    fn test_error_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<Error>();
+    }
+
+    #[test]
+    fn test_parse_error_body() {
+        assert_eq!(ErrorBody::parse(""), ErrorBody::Empty);
+        assert_eq!(ErrorBody::parse("Not Found"), ErrorBody::Text("Not Found".to_string()));
+        assert_eq!(
+            ErrorBody::parse(r#"{"msg": "run not found", "status_code": 404}"#),
+            ErrorBody::Wes(WesErrorResponse {
+                msg: Some("run not found".to_string()),
+                status_code: Some(404),
+            })
+        );
+        assert_eq!(
+            ErrorBody::parse(r#"{"code": 404, "message": "tool not found"}"#),
+            ErrorBody::Trs(TrsError {
+                code: 404,
+                message: Some("tool not found".to_string()),
+            })
+        );
+        assert_eq!(
+            ErrorBody::parse(r#"{"error": "Internal Server Error"}"#),
+            ErrorBody::Json(serde_json::json!({"error": "Internal Server Error"}))
+        );
+    }
+
+    #[test]
+    fn test_from_json_reports_path() {
+        let result = from_json::<TesTask>(r#"{"id": "123", "state": "NOT_A_STATE"}"#);
+        match result {
+            Err(Error::Deserialization { path, .. }) => assert_eq!(path, "state"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -2,5 +2,8 @@
 #[macro_use]
 extern crate serde_derive;
 
+pub mod error;
 pub mod utils;
-pub mod clients;
\ No newline at end of file
+pub mod clients;
+
+pub use error::{Error, ErrorBody, Result};
@@ -0,0 +1,167 @@
+use crate::utils::backend::HttpRequest;
+use crate::utils::configuration::{ApiKey, ApiKeyLocation, Configuration};
+use crate::{Error, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION};
+
+/// Authentication applied to outgoing requests.
+///
+/// The `Authentication` enum resolves which of the credentials stored in a `Configuration`
+/// is sent to the server. Only one credential is applied per request, picked in the
+/// following order of precedence:
+///
+/// 1. `bearer_access_token` – sent as `Authorization: Bearer <token>`
+/// 2. `oauth_access_token` – sent as `Authorization: Bearer <token>`
+/// 3. `basic_auth` – sent as `Authorization: Basic <base64(username:password)>`
+/// 4. `api_key` – sent in the header or query parameter named by its `location`,
+///    prefixed with `prefix` if one is set
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::auth::Authentication;
+/// use ga4gh_sdk::utils::configuration::{BasicAuth, Configuration};
+///
+/// let config = Configuration::new(url::Url::parse("https://api.example.com").unwrap())
+///     .with_basic_auth(BasicAuth {
+///         username: "admin".to_owned(),
+///         password: Some("password".to_owned()),
+///     })
+///     .with_bearer_access_token("my_token".to_owned());
+///
+/// // The bearer token takes precedence over basic authentication
+/// assert_eq!(
+///     Authentication::from_config(&config),
+///     Some(Authentication::Bearer("my_token".to_owned()))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Authentication {
+    /// A bearer (or OAuth access) token.
+    Bearer(String),
+    /// HTTP basic authentication credentials.
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    /// An API key sent in a header or a query parameter.
+    ApiKey(ApiKey),
+}
+
+impl Authentication {
+    /// Resolves the credential to use for the given configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration holding the credentials.
+    ///
+    /// # Returns
+    ///
+    /// The credential with the highest precedence, or `None` if no credential is configured.
+    pub fn from_config(config: &Configuration) -> Option<Self> {
+        if let Some(ref token) = config.bearer_access_token {
+            return Some(Authentication::Bearer(token.clone()));
+        }
+        if let Some(ref token) = config.oauth_access_token {
+            return Some(Authentication::Bearer(token.clone()));
+        }
+        if let Some(ref basic_auth) = config.basic_auth {
+            return Some(Authentication::Basic {
+                username: basic_auth.username.clone(),
+                password: basic_auth.password.clone(),
+            });
+        }
+        config.api_key.clone().map(Authentication::ApiKey)
+    }
+
+    /// Applies the credential to a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request to authenticate.
+    ///
+    /// # Returns
+    ///
+    /// An error if the credential cannot be sent in an HTTP header.
+    pub fn apply(&self, request: &mut HttpRequest) -> Result<()> {
+        match self {
+            Authentication::Bearer(token) => {
+                request.headers.insert(AUTHORIZATION, header_value(&format!("Bearer {}", token))?);
+            }
+            Authentication::Basic { username, password } => {
+                let credentials = format!("{}:{}", username, password.as_deref().unwrap_or_default());
+                let value = format!("Basic {}", STANDARD.encode(credentials));
+                request.headers.insert(AUTHORIZATION, header_value(&value)?);
+            }
+            Authentication::ApiKey(api_key) => {
+                let value = match api_key.prefix {
+                    Some(ref prefix) => format!("{} {}", prefix, api_key.key),
+                    None => api_key.key.clone(),
+                };
+                match api_key.location {
+                    ApiKeyLocation::Header(ref name) => {
+                        let name = HeaderName::from_bytes(name.as_bytes())
+                            .map_err(|e| Error::Configuration(format!("Invalid API key header name: {}", e)))?;
+                        request.headers.insert(name, header_value(&value)?);
+                    }
+                    ApiKeyLocation::Query(ref name) => {
+                        request.url.query_pairs_mut().append_pair(name, &value);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builds a sensitive header value, so that it is not printed in debug output.
+fn header_value(value: &str) -> Result<HeaderValue> {
+    let mut value = HeaderValue::from_str(value)
+        .map_err(|e| Error::Configuration(format!("Invalid credential: {}", e)))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::configuration::BasicAuth;
+    use url::Url;
+
+    fn config() -> Configuration {
+        Configuration::new(Url::parse("https://api.example.com").unwrap())
+    }
+
+    #[test]
+    fn test_no_credentials() {
+        assert_eq!(Authentication::from_config(&config()), None);
+    }
+
+    #[test]
+    fn test_precedence() {
+        let basic_auth = BasicAuth {
+            username: "admin".to_owned(),
+            password: Some("password".to_owned()),
+        };
+        let api_key = ApiKey::new("secret".to_owned());
+
+        let config = config().with_api_key(api_key.clone());
+        assert_eq!(Authentication::from_config(&config), Some(Authentication::ApiKey(api_key)));
+
+        let config = config.with_basic_auth(basic_auth);
+        assert_eq!(
+            Authentication::from_config(&config),
+            Some(Authentication::Basic {
+                username: "admin".to_owned(),
+                password: Some("password".to_owned()),
+            })
+        );
+
+        let config = config.with_oauth_access_token("oauth".to_owned());
+        assert_eq!(Authentication::from_config(&config), Some(Authentication::Bearer("oauth".to_owned())));
+
+        let config = config.with_bearer_access_token("bearer".to_owned());
+        assert_eq!(Authentication::from_config(&config), Some(Authentication::Bearer("bearer".to_owned())));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::utils::body::Body;
+use crate::utils::configuration::Configuration;
+use crate::utils::pool::{HttpVersion, PoolConfig};
+use crate::utils::proxy::ProxyConfig;
+use crate::utils::registry::Registry;
+use crate::utils::timeouts::TimeoutConfig;
+use crate::utils::tls::TlsConfig;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+use reqwest::header::CONTENT_LENGTH;
+
+pub use reqwest::header::HeaderMap;
+pub use reqwest::{Method, StatusCode};
+
+/// An HTTP request as prepared by the `Transport`.
+///
+/// The URL already includes the query parameters, and the headers already include the
+/// user agent. The credentials are added by the `AuthMiddleware` on the way to the backend.
+#[derive(Debug)]
+pub struct HttpRequest {
+    /// The HTTP method of the request.
+    pub method: Method,
+    /// The full URL of the request, including the query string.
+    pub url: Url,
+    /// The request headers.
+    pub headers: HeaderMap,
+    /// The request body, if any.
+    pub body: Option<Body>,
+    /// The maximum time for this request, overriding the configured request timeout.
+    pub timeout: Option<Duration>,
+    /// Whether the `RetryMiddleware` reads the whole response body before returning the
+    /// response, so that failures reading it are retried like failures sending the request.
+    /// Set for every request except downloads streamed with `Transport::get_stream`.
+    pub buffer_response: bool,
+}
+
+impl HttpRequest {
+    /// Creates a new request without headers or body.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method of the request.
+    /// * `url` - The full URL of the request.
+    ///
+    /// # Returns
+    ///
+    /// A new `HttpRequest` instance.
+    pub fn new(method: Method, url: Url) -> Self {
+        HttpRequest {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+            timeout: None,
+            buffer_response: false,
+        }
+    }
+
+    /// Returns a copy of the request, or `None` if its body is streamed and can only be sent once.
+    pub fn try_clone(&self) -> Option<HttpRequest> {
+        let body = match self.body {
+            Some(ref body) => Some(body.try_clone()?),
+            None => None,
+        };
+        Some(HttpRequest {
+            method: self.method.clone(),
+            url: self.url.clone(),
+            headers: self.headers.clone(),
+            body,
+            timeout: self.timeout,
+            buffer_response: self.buffer_response,
+        })
+    }
+}
+
+/// An HTTP response as returned by an `HttpBackend`.
+#[derive(Debug)]
+pub struct HttpResponse {
+    /// The HTTP status code of the response.
+    pub status: StatusCode,
+    /// The response headers.
+    pub headers: HeaderMap,
+    /// The response body, which may still be streaming in.
+    pub body: Body,
+}
+
+impl HttpResponse {
+    /// Creates a new response without headers.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The HTTP status code of the response.
+    /// * `body` - The response body.
+    ///
+    /// # Returns
+    ///
+    /// A new `HttpResponse` instance.
+    pub fn new(status: StatusCode, body: impl Into<Body>) -> Self {
+        HttpResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+}
+
+/// The backend that sends HTTP requests on behalf of a `Transport`.
+///
+/// Implement this trait to run the clients against an in-memory fake, a recording proxy,
+/// or a preconfigured HTTP client. Non-success status codes must be returned as responses,
+/// not as errors; the `Transport` turns them into `Error::Http`. Backends may return the
+/// response as soon as the headers arrive and stream the body.
+///
+/// # Examples
+///
+/// ```
+/// use async_trait::async_trait;
+/// use ga4gh_sdk::utils::backend::{HttpBackend, HttpRequest, HttpResponse, StatusCode};
+/// use ga4gh_sdk::utils::configuration::Configuration;
+/// use ga4gh_sdk::utils::transport::Transport;
+/// use std::sync::Arc;
+///
+/// struct FakeBackend;
+///
+/// #[async_trait]
+/// impl HttpBackend for FakeBackend {
+///     async fn execute(&self, request: HttpRequest) -> ga4gh_sdk::Result<HttpResponse> {
+///         Ok(HttpResponse::new(StatusCode::OK, format!("you asked for {}", request.url.path())))
+///     }
+/// }
+///
+/// # async fn example() -> ga4gh_sdk::Result<()> {
+/// let config = Configuration::new(url::Url::parse("https://api.example.com").unwrap());
+/// let transport = Transport::with_backend(&config, Arc::new(FakeBackend))?;
+/// assert_eq!(transport.get("/users", None).await?, "you asked for /users");
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait HttpBackend: Send + Sync {
+    /// Sends the request and returns the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request to send.
+    ///
+    /// # Returns
+    ///
+    /// The response, whatever its status code, or an error if no response was received.
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse>;
+}
+
+/// The default `HttpBackend`, sending requests with `reqwest`.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestBackend {
+    client: reqwest::Client,
+    read_timeout: Option<Duration>,
+}
+
+/// The settings a `reqwest::Client` is built from.
+#[derive(PartialEq)]
+struct ClientSettings {
+    tls: TlsConfig,
+    proxy: ProxyConfig,
+    timeouts: TimeoutConfig,
+    pool: PoolConfig,
+}
+
+/// The backends in use, one per distinct set of client settings.
+static SHARED_BACKENDS: Registry<ClientSettings, ReqwestBackend> = Registry::new();
+
+impl ReqwestBackend {
+    /// Creates a new backend with a default `reqwest::Client`.
+    pub fn new() -> Self {
+        ReqwestBackend::default()
+    }
+
+    /// Creates a new backend sending requests with the given client.
+    ///
+    /// Use this to reuse a client preconfigured with, e.g., custom TLS settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client used to send the requests.
+    ///
+    /// # Returns
+    ///
+    /// A new `ReqwestBackend` instance.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        ReqwestBackend {
+            client,
+            read_timeout: None,
+        }
+    }
+
+    /// Creates a new backend with a client set up from the given configuration.
+    ///
+    /// The backend has its own connection pool; use `ReqwestBackend::shared` to share it.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration holding the TLS, proxy, timeout and pool settings.
+    ///
+    /// # Returns
+    ///
+    /// A new `ReqwestBackend` instance, or `Error::Configuration` if the settings are invalid.
+    pub fn from_config(config: &Configuration) -> Result<Self> {
+        let mut builder = config.proxy.apply(reqwest::Client::builder())?;
+        if !config.tls.is_default() {
+            let mut tls = config.tls.client_config()?;
+            tls.alpn_protocols = match config.pool.http_version {
+                HttpVersion::Auto => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+                HttpVersion::Http1 => vec![b"http/1.1".to_vec()],
+                HttpVersion::Http2 => vec![b"h2".to_vec()],
+            };
+            builder = builder.use_preconfigured_tls(tls);
+        }
+        if let Some(connect) = config.timeouts.connect {
+            builder = builder.connect_timeout(connect);
+        }
+        if let Some(request) = config.timeouts.request {
+            builder = builder.timeout(request);
+        }
+        builder = builder
+            .pool_idle_timeout(config.pool.idle_timeout)
+            .pool_max_idle_per_host(config.pool.max_idle_per_host);
+        builder = match config.pool.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2 => builder.http2_prior_knowledge(),
+        };
+        let client = builder
+            .build()
+            .map_err(|e| Error::Configuration(format!("Failed to build HTTP client: {}", e)))?;
+        Ok(ReqwestBackend {
+            client,
+            read_timeout: config.timeouts.read,
+        })
+    }
+
+    /// Returns a backend for the given configuration, sharing its connection pool with all
+    /// other backends returned for the same TLS, proxy, timeout and pool settings. The backend
+    /// is dropped with the last transport using it.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration holding the connection settings.
+    ///
+    /// # Returns
+    ///
+    /// The shared `ReqwestBackend`, or `Error::Configuration` if the settings are invalid.
+    pub fn shared(config: &Configuration) -> Result<Arc<Self>> {
+        let settings = ClientSettings {
+            tls: config.tls.clone(),
+            proxy: config.proxy.clone(),
+            timeouts: config.timeouts.clone(),
+            pool: config.pool.clone(),
+        };
+        SHARED_BACKENDS.get_or_try_insert(settings, || ReqwestBackend::from_config(config))
+    }
+
+    /// Awaits `future`, failing with `Error::Timeout` if the read timeout expires first.
+    async fn read<T>(read_timeout: Option<Duration>, future: impl Future<Output = reqwest::Result<T>>) -> Result<T> {
+        match read_timeout {
+            Some(read_timeout) => Ok(tokio::time::timeout(read_timeout, future).await??),
+            None => Ok(future.await?),
+        }
+    }
+}
+
+#[async_trait]
+impl HttpBackend for ReqwestBackend {
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let mut request_builder = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers);
+        if let Some(body) = request.body {
+            if let Some(length) = body.length() {
+                request_builder = request_builder.header(CONTENT_LENGTH, length);
+            }
+            request_builder = match body.as_bytes() {
+                Some(bytes) => request_builder.body(bytes.clone()),
+                None => request_builder.body(reqwest::Body::wrap_stream(body.into_stream())),
+            };
+        }
+        if let Some(timeout) = request.timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        let read_timeout = self.read_timeout;
+        let resp = ReqwestBackend::read(read_timeout, request_builder.send()).await?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = futures::stream::try_unfold(resp, move |mut resp| async move {
+            let chunk = ReqwestBackend::read(read_timeout, resp.chunk()).await?;
+            Ok(chunk.map(|chunk| (chunk, resp)))
+        });
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: Body::from_stream(body),
+        })
+    }
+}
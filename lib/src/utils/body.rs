@@ -0,0 +1,187 @@
+use crate::{Error, Result};
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt, TryStreamExt};
+use std::fmt;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Mutex;
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+/// A stream of body chunks.
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>;
+
+/// The body of an `HttpRequest` or `HttpResponse`, either fully in memory or streamed.
+///
+/// Streamed bodies can only be read once, so requests with a streamed body are never retried.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::body::Body;
+///
+/// # async fn example() -> ga4gh_sdk::Result<()> {
+/// let body = Body::from(r#"{"name": "hello"}"#);
+/// assert_eq!(body.as_bytes().unwrap().len(), 17);
+///
+/// let chunks = futures::stream::iter(vec![Ok("hel".into()), Ok("lo".into())]);
+/// let body = Body::from_stream(chunks);
+/// assert_eq!(body.collect().await?, "hello");
+/// # Ok(())
+/// # }
+/// ```
+pub struct Body {
+    inner: Inner,
+}
+
+enum Inner {
+    Bytes(Bytes),
+    // the mutex only makes the body `Sync`; the stream is never shared
+    Stream {
+        stream: Mutex<ByteStream>,
+        length: Option<u64>,
+    },
+}
+
+impl Body {
+    /// Creates an empty body.
+    pub fn empty() -> Self {
+        Body::from(Bytes::new())
+    }
+
+    /// Creates a body streamed from the given chunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The chunks of the body.
+    pub fn from_stream(stream: impl Stream<Item = Result<Bytes>> + Send + 'static) -> Self {
+        Body {
+            inner: Inner::Stream {
+                stream: Mutex::new(Box::pin(stream)),
+                length: None,
+            },
+        }
+    }
+
+    /// Creates a body streamed from the given reader.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader the body is read from.
+    pub fn from_reader(reader: impl AsyncRead + Send + 'static) -> Self {
+        Body::from_stream(ReaderStream::new(reader).map_err(Error::Io))
+    }
+
+    /// Creates a body streamed from the file at the given path, with its length set.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file cannot be opened.
+    pub async fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let file = tokio::fs::File::open(path).await?;
+        let length = file.metadata().await?.len();
+        Ok(Body::from_reader(file).with_length(length))
+    }
+
+    /// Sets the length of a streamed body, so it can be sent with a `Content-Length` header
+    /// instead of chunked. Has no effect on bodies in memory, whose length is known.
+    pub fn with_length(mut self, length: u64) -> Self {
+        if let Inner::Stream { length: ref mut l, .. } = self.inner {
+            *l = Some(length);
+        }
+        self
+    }
+
+    /// Returns the length of the body, if known.
+    pub fn length(&self) -> Option<u64> {
+        match self.inner {
+            Inner::Bytes(ref bytes) => Some(bytes.len() as u64),
+            Inner::Stream { length, .. } => length,
+        }
+    }
+
+    /// Returns the body if it is in memory, or `None` if it is streamed.
+    pub fn as_bytes(&self) -> Option<&Bytes> {
+        match self.inner {
+            Inner::Bytes(ref bytes) => Some(bytes),
+            Inner::Stream { .. } => None,
+        }
+    }
+
+    /// Returns a copy of the body if it is in memory, or `None` if it is streamed.
+    pub fn try_clone(&self) -> Option<Body> {
+        self.as_bytes().cloned().map(Body::from)
+    }
+
+    /// Reads the whole body into memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first chunk that fails.
+    pub async fn collect(self) -> Result<Bytes> {
+        match self.inner {
+            Inner::Bytes(bytes) => Ok(bytes),
+            Inner::Stream { stream, .. } => {
+                let mut stream = stream.into_inner().unwrap_or_else(|e| e.into_inner());
+                let mut body = BytesMut::new();
+                while let Some(chunk) = stream.next().await {
+                    body.extend_from_slice(&chunk?);
+                }
+                Ok(body.freeze())
+            }
+        }
+    }
+
+    /// Turns the body into a stream of chunks.
+    pub fn into_stream(self) -> ByteStream {
+        match self.inner {
+            Inner::Bytes(bytes) => Box::pin(futures::stream::once(async move { Ok(bytes) })),
+            Inner::Stream { stream, .. } => stream.into_inner().unwrap_or_else(|e| e.into_inner()),
+        }
+    }
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Body::empty()
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.inner {
+            Inner::Bytes(ref bytes) => f.debug_tuple("Body").field(bytes).finish(),
+            Inner::Stream { length, .. } => f.debug_struct("Body").field("stream_length", &length).finish(),
+        }
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(bytes: Bytes) -> Self {
+        Body {
+            inner: Inner::Bytes(bytes),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Body::from(Bytes::from(bytes))
+    }
+}
+
+impl From<String> for Body {
+    fn from(text: String) -> Self {
+        Body::from(Bytes::from(text))
+    }
+}
+
+impl From<&'static str> for Body {
+    fn from(text: &'static str) -> Self {
+        Body::from(Bytes::from_static(text.as_bytes()))
+    }
+}
@@ -0,0 +1,509 @@
+use crate::utils::backend::{HeaderMap, HttpRequest, HttpResponse, Method, StatusCode};
+use crate::utils::body::Body;
+use crate::utils::middleware::{Middleware, Next};
+use crate::Result;
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures::StreamExt;
+use reqwest::header::{
+    HeaderValue, AGE, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RANGE, VARY,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::debug;
+use url::Url;
+
+/// Settings of the response cache for `GET` requests.
+///
+/// Disabled by default. When enabled, successful responses carrying an `ETag`, a
+/// `Last-Modified` date or a `Cache-Control: max-age` are kept in memory. Responses still
+/// fresh according to their `max-age` are served without contacting the service; stale ones
+/// are revalidated with `If-None-Match` / `If-Modified-Since`, and a `304 Not Modified`
+/// answer serves the cached body. Responses with `Cache-Control: no-store` are never kept.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::cache::CacheConfig;
+/// use ga4gh_sdk::utils::configuration::Configuration;
+///
+/// let cache = CacheConfig::enabled().with_max_entries(500).with_max_bytes(32 * 1024 * 1024);
+/// let config = Configuration::new(url::Url::parse("https://tes.example.org").unwrap())
+///     .with_cache(cache);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default = "CacheConfig::enabled")]
+pub struct CacheConfig {
+    /// Whether responses are cached. A `cache` object in the configuration file enables it.
+    pub enabled: bool,
+    /// The maximum number of responses kept.
+    pub max_entries: usize,
+    /// The maximum total size of the kept response bodies, in bytes. Larger responses are
+    /// never kept.
+    pub max_bytes: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            enabled: false,
+            max_entries: 256,
+            max_bytes: 16 * 1024 * 1024,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Returns the default settings with the cache enabled.
+    pub fn enabled() -> Self {
+        CacheConfig {
+            enabled: true,
+            ..CacheConfig::default()
+        }
+    }
+
+    /// Sets the maximum number of responses kept.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Sets the maximum total size of the kept response bodies, in bytes.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+}
+
+/// A cached response, with the validators and freshness sent by the service.
+#[derive(Debug)]
+struct Entry {
+    headers: HeaderMap,
+    body: Bytes,
+    fresh_until: Instant,
+    // for evicting the least recently used entry
+    last_used: u64,
+}
+
+impl Entry {
+    fn has_validator(&self) -> bool {
+        self.headers.contains_key(ETAG) || self.headers.contains_key(LAST_MODIFIED)
+    }
+
+    fn response(&self) -> HttpResponse {
+        HttpResponse {
+            status: StatusCode::OK,
+            headers: self.headers.clone(),
+            body: Body::from(self.body.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    entries: HashMap<String, Entry>,
+    size: usize,
+    clock: u64,
+}
+
+/// An in-memory store of `GET` responses, bounded by a `CacheConfig`.
+#[derive(Debug)]
+pub struct ResponseCache {
+    config: CacheConfig,
+    entries: Mutex<Entries>,
+}
+
+impl ResponseCache {
+    /// Creates an empty cache.
+    pub fn new(config: &CacheConfig) -> Self {
+        ResponseCache {
+            config: config.clone(),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Returns the number of cached responses.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns whether no response is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached responses.
+    pub fn clear(&self) {
+        let mut entries = self.lock();
+        entries.entries.clear();
+        entries.size = 0;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the cached response for `key` if it is fresh, or the validators to revalidate it.
+    fn lookup(&self, key: &str) -> Lookup {
+        let mut entries = self.lock();
+        entries.clock += 1;
+        let clock = entries.clock;
+        match entries.entries.get_mut(key) {
+            Some(entry) => {
+                entry.last_used = clock;
+                if Instant::now() < entry.fresh_until {
+                    Lookup::Fresh(entry.response())
+                } else if entry.has_validator() {
+                    Lookup::Stale {
+                        etag: entry.headers.get(ETAG).cloned(),
+                        last_modified: entry.headers.get(LAST_MODIFIED).cloned(),
+                    }
+                } else {
+                    Lookup::Miss
+                }
+            }
+            None => Lookup::Miss,
+        }
+    }
+
+    /// Refreshes a cached response after the service answered `304 Not Modified`.
+    fn revalidated(&self, key: &str, headers: &HeaderMap) -> Option<HttpResponse> {
+        let mut entries = self.lock();
+        let entry = entries.entries.get_mut(key)?;
+        for name in [CACHE_CONTROL, ETAG, LAST_MODIFIED] {
+            if let Some(value) = headers.get(&name) {
+                entry.headers.insert(name, value.clone());
+            }
+        }
+        entry.fresh_until = fresh_until(&entry.headers);
+        Some(entry.response())
+    }
+
+    fn insert(&self, key: String, headers: HeaderMap, body: Bytes) {
+        let mut entries = self.lock();
+        if let Some(old) = entries.entries.remove(&key) {
+            entries.size -= old.body.len();
+        }
+        while !entries.entries.is_empty()
+            && (entries.entries.len() >= self.config.max_entries
+                || entries.size + body.len() > self.config.max_bytes)
+        {
+            let oldest = entries
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest.and_then(|oldest| entries.entries.remove(&oldest)) {
+                entries.size -= oldest.body.len();
+            }
+        }
+        if self.config.max_entries == 0 {
+            return;
+        }
+        entries.clock += 1;
+        entries.size += body.len();
+        let entry = Entry {
+            fresh_until: fresh_until(&headers),
+            headers,
+            body,
+            last_used: entries.clock,
+        };
+        entries.entries.insert(key, entry);
+    }
+
+    /// Removes the responses for `path` and, for custom methods such as `/tasks/1:cancel`,
+    /// for the resource they act on.
+    fn invalidate(&self, url: &Url) {
+        let path = url.path();
+        let resource = path.rsplit_once(':').map_or(path, |(resource, _)| resource);
+        let mut entries = self.lock();
+        let stale: Vec<String> = entries
+            .entries
+            .keys()
+            .filter(|key| {
+                Url::parse(key).is_ok_and(|cached| {
+                    cached.origin() == url.origin() && (cached.path() == path || cached.path() == resource)
+                })
+            })
+            .cloned()
+            .collect();
+        for key in stale {
+            if let Some(entry) = entries.entries.remove(&key) {
+                entries.size -= entry.body.len();
+            }
+        }
+    }
+}
+
+enum Lookup {
+    Fresh(HttpResponse),
+    Stale {
+        etag: Option<HeaderValue>,
+        last_modified: Option<HeaderValue>,
+    },
+    Miss,
+}
+
+/// Returns the `Cache-Control` directives of the given headers, in lower case.
+fn cache_directives(headers: &HeaderMap) -> Vec<String> {
+    headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|directive| directive.trim().to_ascii_lowercase())
+        .collect()
+}
+
+/// Returns until when a response is fresh, from its `max-age` and `Age`.
+fn fresh_until(headers: &HeaderMap) -> Instant {
+    let directives = cache_directives(headers);
+    if directives.iter().any(|d| d == "no-cache") {
+        return Instant::now();
+    }
+    let max_age = directives
+        .iter()
+        .find_map(|d| d.strip_prefix("max-age=")?.parse::<u64>().ok())
+        .unwrap_or(0);
+    let age = headers
+        .get(AGE)
+        .and_then(|age| age.to_str().ok()?.parse::<u64>().ok())
+        .unwrap_or(0);
+    Instant::now() + Duration::from_secs(max_age.saturating_sub(age))
+}
+
+/// Returns whether a successful response may be kept.
+fn is_cacheable(response: &HttpResponse) -> bool {
+    if response.status != StatusCode::OK {
+        return false;
+    }
+    let directives = cache_directives(&response.headers);
+    if directives.iter().any(|d| d == "no-store") {
+        return false;
+    }
+    if response.headers.get(VARY).is_some_and(|vary| vary == "*") {
+        return false;
+    }
+    response.headers.contains_key(ETAG)
+        || response.headers.contains_key(LAST_MODIFIED)
+        || directives.iter().any(|d| d.starts_with("max-age="))
+}
+
+/// Serves `GET` requests from a `ResponseCache` and revalidates stale responses.
+///
+/// Part of the default chain when the configuration enables the cache, at its start so fresh
+/// responses skip retries, rate limits and the network. Requests for a byte range or with
+/// their own conditional headers bypass the cache, and successful write requests drop the
+/// cached responses of the resource they target.
+#[derive(Debug, Clone)]
+pub struct CacheMiddleware {
+    cache: Arc<ResponseCache>,
+}
+
+impl CacheMiddleware {
+    /// Creates a middleware caching responses in the given cache.
+    pub fn new(cache: Arc<ResponseCache>) -> Self {
+        CacheMiddleware { cache }
+    }
+
+    /// Reads the body of a response to keep it, unless it grows larger than the cache.
+    async fn store(&self, key: String, response: HttpResponse) -> Result<HttpResponse> {
+        let HttpResponse { status, headers, body } = response;
+        if body.length().is_some_and(|length| length > self.cache.config.max_bytes as u64) {
+            return Ok(HttpResponse { status, headers, body });
+        }
+
+        let mut stream = body.into_stream();
+        let mut content = BytesMut::new();
+        while let Some(chunk) = stream.next().await {
+            content.extend_from_slice(&chunk?);
+            if content.len() > self.cache.config.max_bytes {
+                // too large to keep: hand out what was read and the rest of the stream
+                let read = futures::stream::once(async move { Ok(content.freeze()) });
+                let body = Body::from_stream(read.chain(stream));
+                return Ok(HttpResponse { status, headers, body });
+            }
+        }
+        let content = content.freeze();
+        self.cache.insert(key, headers.clone(), content.clone());
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: Body::from(content),
+        })
+    }
+}
+
+#[async_trait]
+impl Middleware for CacheMiddleware {
+    async fn handle(&self, mut request: HttpRequest, next: Next<'_>) -> Result<HttpResponse> {
+        if request.method != Method::GET {
+            let is_safe = matches!(request.method, Method::HEAD | Method::OPTIONS);
+            let url = request.url.clone();
+            let result = next.run(request).await;
+            if !is_safe && result.as_ref().is_ok_and(|response| response.status.is_success()) {
+                self.cache.invalidate(&url);
+            }
+            return result;
+        }
+        let bypass = [RANGE, IF_NONE_MATCH, IF_MODIFIED_SINCE]
+            .iter()
+            .any(|name| request.headers.contains_key(name))
+            || cache_directives(&request.headers).iter().any(|d| d == "no-store");
+        if bypass {
+            return next.run(request).await;
+        }
+
+        let key = request.url.to_string();
+        let revalidating = match self.cache.lookup(&key) {
+            Lookup::Fresh(response) => {
+                debug!("Serving {} from the cache", request.url);
+                return Ok(response);
+            }
+            Lookup::Stale { etag, last_modified } => {
+                if let Some(etag) = etag {
+                    request.headers.insert(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = last_modified {
+                    request.headers.insert(IF_MODIFIED_SINCE, last_modified);
+                }
+                true
+            }
+            Lookup::Miss => false,
+        };
+
+        let response = next.run(request).await?;
+        if revalidating && response.status == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = self.cache.revalidated(&key, &response.headers) {
+                debug!("Cached response for {} is still valid", key);
+                return Ok(cached);
+            }
+        }
+        if is_cacheable(&response) {
+            self.store(key, response).await
+        } else {
+            Ok(response)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::backend::HttpBackend;
+    use crate::utils::configuration::Configuration;
+    use crate::utils::transport::Transport;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A backend serving a versioned document per path, honouring `If-None-Match`.
+    struct DocumentBackend {
+        cache_control: &'static str,
+        requests: AtomicUsize,
+        not_modified: AtomicUsize,
+    }
+
+    impl DocumentBackend {
+        fn new(cache_control: &'static str) -> Arc<Self> {
+            Arc::new(DocumentBackend {
+                cache_control,
+                requests: AtomicUsize::new(0),
+                not_modified: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl HttpBackend for DocumentBackend {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let etag = format!("\"{}\"", request.url.path());
+            if request.headers.get(IF_NONE_MATCH).is_some_and(|v| v == etag.as_str()) {
+                self.not_modified.fetch_add(1, Ordering::SeqCst);
+                return Ok(HttpResponse::new(StatusCode::NOT_MODIFIED, ""));
+            }
+            let mut response = HttpResponse::new(StatusCode::OK, format!("document {}", request.url.path()));
+            response.headers.insert(ETAG, etag.parse().unwrap());
+            response.headers.insert(CACHE_CONTROL, self.cache_control.parse().unwrap());
+            Ok(response)
+        }
+    }
+
+    fn cached_transport(backend: Arc<DocumentBackend>, cache: CacheConfig) -> Transport {
+        let config = Configuration::new(Url::parse("https://api.example.com").unwrap()).with_cache(cache);
+        Transport::with_backend(&config, backend).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_revalidate_with_etag() {
+        let backend = DocumentBackend::new("no-cache");
+        let transport = cached_transport(backend.clone(), CacheConfig::enabled());
+
+        assert_eq!(transport.get("/tasks/1", None).await.unwrap(), "document /tasks/1");
+        assert_eq!(transport.get("/tasks/1", None).await.unwrap(), "document /tasks/1");
+
+        assert_eq!(backend.requests.load(Ordering::SeqCst), 2);
+        assert_eq!(backend.not_modified.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fresh_response_served_from_cache() {
+        let backend = DocumentBackend::new("max-age=60");
+        let transport = cached_transport(backend.clone(), CacheConfig::enabled());
+
+        for _ in 0..3 {
+            assert_eq!(transport.get("/service-info", None).await.unwrap(), "document /service-info");
+        }
+        assert_eq!(backend.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_by_default() {
+        let backend = DocumentBackend::new("max-age=60");
+        let transport = cached_transport(backend.clone(), CacheConfig::default());
+
+        transport.get("/service-info", None).await.unwrap();
+        transport.get("/service-info", None).await.unwrap();
+        assert_eq!(backend.requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_entries_bounded() {
+        let backend = DocumentBackend::new("max-age=60");
+        let transport = cached_transport(backend.clone(), CacheConfig::enabled().with_max_entries(1));
+
+        transport.get("/tasks/1", None).await.unwrap();
+        transport.get("/tasks/2", None).await.unwrap();
+        // the first document was evicted
+        transport.get("/tasks/1", None).await.unwrap();
+        assert_eq!(backend.requests.load(Ordering::SeqCst), 3);
+
+        // a body larger than the cache is returned in full but not kept
+        let small = cached_transport(backend.clone(), CacheConfig::enabled().with_max_bytes(4));
+        assert_eq!(small.get("/tasks/3", None).await.unwrap(), "document /tasks/3");
+        small.get("/tasks/3", None).await.unwrap();
+        assert_eq!(backend.requests.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_write_invalidates_resource() {
+        let backend = DocumentBackend::new("max-age=60");
+        let transport = cached_transport(backend.clone(), CacheConfig::enabled());
+
+        transport.get("/tasks/1", None).await.unwrap();
+        transport.post("/tasks/1:cancel", None).await.unwrap();
+        transport.get("/tasks/1", None).await.unwrap();
+        // GET, POST and a second GET after the cached document was dropped
+        assert_eq!(backend.requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_deserialize_cache() {
+        let cache: CacheConfig = serde_json::from_value(serde_json::json!({ "max_entries": 10 })).unwrap();
+
+        assert!(cache.enabled);
+        assert_eq!(cache.max_entries, 10);
+        assert_eq!(cache.max_bytes, CacheConfig::default().max_bytes);
+    }
+}
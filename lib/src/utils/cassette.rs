@@ -0,0 +1,602 @@
+use crate::utils::backend::{HeaderMap, HttpBackend, HttpRequest, HttpResponse, Method, ReqwestBackend, StatusCode};
+use crate::utils::body::Body;
+use crate::utils::configuration::{ApiKeyLocation, Configuration};
+use crate::utils::registry::Registry;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use bytes::Bytes;
+use reqwest::header::{HeaderName, HeaderValue};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+use url::Url;
+
+/// The value written to a cassette in place of a redacted header or query parameter.
+pub const REDACTED: &str = "REDACTED";
+
+/// Whether a cassette is written from live traffic or served instead of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CassetteMode {
+    /// Send requests to the service and write every interaction to the cassette.
+    Record,
+    /// Answer requests from the cassette without contacting the service.
+    Replay,
+}
+
+/// Settings for recording a `Transport`'s traffic to a cassette file, or replaying it offline.
+///
+/// The file is YAML if its name ends in `.yaml` or `.yml`, and JSON otherwise. The values of
+/// the redacted headers and query parameters are replaced by `REDACTED` before anything is
+/// written; by default these are the usual credential headers plus the API key configured
+/// for the service. In replay mode, requests are matched by method, URL and body, each
+/// recorded interaction is used once, and a request without a match fails with
+/// `Error::Configuration`.
+///
+/// The transports using the same cassette at the same time share it, so a recording holds the
+/// traffic of all clients of a test. Once they are all dropped, the recording is written and
+/// a later transport starts the cassette over.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::cassette::{CassetteConfig, CassetteMode};
+/// use ga4gh_sdk::utils::configuration::Configuration;
+///
+/// let cassette = CassetteConfig::new("tests/cassettes/create_task.yaml", CassetteMode::Replay)
+///     .with_redacted_header("x-project-token");
+/// let config = Configuration::new(url::Url::parse("https://tes.example.org").unwrap())
+///     .with_cassette(cassette);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CassetteConfig {
+    /// The path of the cassette file.
+    pub path: PathBuf,
+    /// Whether the cassette is recorded or replayed.
+    pub mode: CassetteMode,
+    /// The headers whose values are redacted, in addition to the default ones.
+    #[serde(default)]
+    pub redact_headers: Vec<String>,
+    /// The query parameters whose values are redacted.
+    #[serde(default)]
+    pub redact_query: Vec<String>,
+}
+
+/// The headers redacted from every cassette.
+const DEFAULT_REDACTED_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+];
+
+impl CassetteConfig {
+    /// Creates the settings for the cassette at `path`.
+    pub fn new(path: impl Into<PathBuf>, mode: CassetteMode) -> Self {
+        CassetteConfig {
+            path: path.into(),
+            mode,
+            redact_headers: Vec::new(),
+            redact_query: Vec::new(),
+        }
+    }
+
+    /// Adds a header whose value is redacted.
+    pub fn with_redacted_header(mut self, name: impl Into<String>) -> Self {
+        self.redact_headers.push(name.into());
+        self
+    }
+
+    /// Adds a query parameter whose value is redacted.
+    pub fn with_redacted_query(mut self, name: impl Into<String>) -> Self {
+        self.redact_query.push(name.into());
+        self
+    }
+
+    fn is_redacted_header(&self, name: &str) -> bool {
+        DEFAULT_REDACTED_HEADERS.iter().any(|redacted| name.eq_ignore_ascii_case(redacted))
+            || self.redact_headers.iter().any(|redacted| name.eq_ignore_ascii_case(redacted))
+    }
+
+    fn redact_url(&self, url: &Url) -> Url {
+        if self.redact_query.is_empty() || url.query().is_none() {
+            return url.clone();
+        }
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(key, value)| {
+                let value = if self.redact_query.iter().any(|redacted| *redacted == key) {
+                    REDACTED.to_string()
+                } else {
+                    value.into_owned()
+                };
+                (key.into_owned(), value)
+            })
+            .collect();
+        let mut url = url.clone();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+        url
+    }
+
+    fn redact_headers(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        let mut recorded = BTreeMap::new();
+        for name in headers.keys() {
+            let value = if self.is_redacted_header(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                headers
+                    .get_all(name)
+                    .iter()
+                    .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            recorded.insert(name.as_str().to_string(), value);
+        }
+        recorded
+    }
+
+    /// Adds the header or query parameter carrying the configuration's API key, if any.
+    fn redacting_api_key(mut self, config: &Configuration) -> Self {
+        match config.api_key.as_ref().map(|api_key| &api_key.location) {
+            Some(ApiKeyLocation::Header(name)) => self.redact_headers.push(name.clone()),
+            Some(ApiKeyLocation::Query(name)) => self.redact_query.push(name.clone()),
+            None => {}
+        }
+        self
+    }
+}
+
+/// A recorded request and the response it received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request as it was sent, with redactions.
+    pub request: RecordedRequest,
+    /// The response as it was received, with redactions.
+    pub response: RecordedResponse,
+}
+
+/// A request in a cassette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// The HTTP method of the request.
+    pub method: String,
+    /// The full URL of the request.
+    pub url: String,
+    /// The request headers.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// The request body, if any.
+    #[serde(default, flatten)]
+    pub body: RecordedBody,
+}
+
+/// A response in a cassette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// The response headers.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// The response body.
+    #[serde(default, flatten)]
+    pub body: RecordedBody,
+}
+
+/// A body in a cassette: text as is, anything else base64-encoded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedBody {
+    /// The body, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Whether `body` is base64-encoded.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub base64: bool,
+}
+
+impl RecordedBody {
+    fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => RecordedBody {
+                body: Some(text.to_string()),
+                base64: false,
+            },
+            Err(_) => RecordedBody {
+                body: Some(base64::engine::general_purpose::STANDARD.encode(bytes)),
+                base64: true,
+            },
+        }
+    }
+
+    fn bytes(&self) -> Result<Bytes> {
+        match self.body {
+            None => Ok(Bytes::new()),
+            Some(ref body) if self.base64 => base64::engine::general_purpose::STANDARD
+                .decode(body)
+                .map(Bytes::from)
+                .map_err(|e| Error::Configuration(format!("invalid base64 body in cassette: {}", e))),
+            Some(ref body) => Ok(Bytes::from(body.clone())),
+        }
+    }
+
+    /// Returns whether a request body matches this one, comparing JSON bodies by value.
+    fn matches(&self, bytes: &[u8]) -> bool {
+        let Ok(recorded) = self.bytes() else { return false };
+        if recorded == bytes {
+            return true;
+        }
+        match (
+            serde_json::from_slice::<serde_json::Value>(&recorded),
+            serde_json::from_slice::<serde_json::Value>(bytes),
+        ) {
+            (Ok(recorded), Ok(sent)) => recorded == sent,
+            _ => false,
+        }
+    }
+}
+
+/// The contents of a cassette file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    /// The interactions, in the order they happened.
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    fn is_yaml(path: &Path) -> bool {
+        matches!(path.extension().and_then(|e| e.to_str()), Some("yaml" | "yml"))
+    }
+
+    /// Reads a cassette from a JSON or YAML file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file cannot be read, or `Error::Configuration` if it is not
+    /// a valid cassette.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let cassette = if Cassette::is_yaml(path) {
+            serde_yaml::from_str(&content).map_err(|e| e.to_string())
+        } else {
+            serde_json::from_str(&content).map_err(|e| e.to_string())
+        };
+        cassette.map_err(|e| Error::Configuration(format!("Invalid cassette {}: {}", path.display(), e)))
+    }
+
+    /// Writes the cassette to a JSON or YAML file, creating its directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let content = if Cassette::is_yaml(path) {
+            serde_yaml::to_string(self).map_err(|e| Error::Configuration(e.to_string()))?
+        } else {
+            serde_json::to_string_pretty(self).map_err(|e| Error::Configuration(e.to_string()))?
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Sends requests through another backend and records every interaction to a cassette.
+///
+/// The cassette file is started afresh when the backend is created, and the interactions are
+/// written to it once, when the backend is dropped or `flush` is called. Bodies are read into
+/// memory to record them.
+pub struct RecordingBackend {
+    inner: Arc<dyn HttpBackend>,
+    config: CassetteConfig,
+    cassette: Mutex<Cassette>,
+}
+
+impl RecordingBackend {
+    /// Creates a backend recording the traffic of `inner` to the cassette in `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the cassette file cannot be written.
+    pub fn new(inner: Arc<dyn HttpBackend>, config: &CassetteConfig) -> Result<Self> {
+        let cassette = Cassette::default();
+        cassette.save(&config.path)?;
+        Ok(RecordingBackend {
+            inner,
+            config: config.clone(),
+            cassette: Mutex::new(cassette),
+        })
+    }
+
+    /// Writes the interactions recorded so far to the cassette file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file cannot be written.
+    pub fn flush(&self) -> Result<()> {
+        self.cassette.lock().unwrap_or_else(|e| e.into_inner()).save(&self.config.path)
+    }
+}
+
+impl Drop for RecordingBackend {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Failed to write cassette {}: {}", self.config.path.display(), e);
+        }
+    }
+}
+
+#[async_trait]
+impl HttpBackend for RecordingBackend {
+    async fn execute(&self, mut request: HttpRequest) -> Result<HttpResponse> {
+        let request_body = match request.body.take() {
+            Some(body) => Some(body.collect().await?),
+            None => None,
+        };
+        let recorded_request = RecordedRequest {
+            method: request.method.to_string(),
+            url: self.config.redact_url(&request.url).to_string(),
+            headers: self.config.redact_headers(&request.headers),
+            body: request_body.as_deref().map(RecordedBody::new).unwrap_or_default(),
+        };
+        request.body = request_body.map(Body::from);
+
+        let response = self.inner.execute(request).await?;
+        let HttpResponse { status, headers, body } = response;
+        let body = body.collect().await?;
+        let recorded_response = RecordedResponse {
+            status: status.as_u16(),
+            headers: self.config.redact_headers(&headers),
+            body: RecordedBody::new(&body),
+        };
+
+        let mut cassette = self.cassette.lock().unwrap_or_else(|e| e.into_inner());
+        cassette.interactions.push(Interaction {
+            request: recorded_request,
+            response: recorded_response,
+        });
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: Body::from(body),
+        })
+    }
+}
+
+/// Answers requests from a recorded cassette, without network access.
+pub struct ReplayBackend {
+    config: CassetteConfig,
+    interactions: Vec<Interaction>,
+    used: Mutex<Vec<bool>>,
+}
+
+impl ReplayBackend {
+    /// Creates a backend replaying the cassette in `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the cassette file cannot be read, or `Error::Configuration` if
+    /// it is not a valid cassette.
+    pub fn new(config: &CassetteConfig) -> Result<Self> {
+        let cassette = Cassette::load(&config.path)?;
+        Ok(ReplayBackend {
+            config: config.clone(),
+            used: Mutex::new(vec![false; cassette.interactions.len()]),
+            interactions: cassette.interactions,
+        })
+    }
+
+    /// Returns the number of recorded interactions that were not replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.used.lock().unwrap_or_else(|e| e.into_inner()).iter().filter(|used| !**used).count()
+    }
+
+    fn response(interaction: &Interaction) -> Result<HttpResponse> {
+        let recorded = &interaction.response;
+        let status = StatusCode::from_u16(recorded.status)
+            .map_err(|e| Error::Configuration(format!("invalid status in cassette: {}", e)))?;
+        let mut response = HttpResponse::new(status, recorded.body.bytes()?);
+        for (name, value) in &recorded.headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::try_from(name.as_str()), HeaderValue::from_str(value)) {
+                response.headers.insert(name, value);
+            }
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl HttpBackend for ReplayBackend {
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let url = self.config.redact_url(&request.url).to_string();
+        let body = match request.body {
+            Some(body) => body.collect().await?,
+            None => Bytes::new(),
+        };
+
+        let mut used = self.used.lock().unwrap_or_else(|e| e.into_inner());
+        let found = self.interactions.iter().enumerate().find(|(i, interaction)| {
+            !used[*i]
+                && interaction.request.method.parse::<Method>().is_ok_and(|method| method == request.method)
+                && interaction.request.url == url
+                && interaction.request.body.matches(&body)
+        });
+        match found {
+            Some((i, interaction)) => {
+                used[i] = true;
+                ReplayBackend::response(interaction)
+            }
+            None => Err(Error::Configuration(format!(
+                "No recorded response left in cassette {} for {} {}",
+                self.config.path.display(),
+                request.method,
+                url
+            ))),
+        }
+    }
+}
+
+/// The cassettes being recorded, one per cassette.
+static RECORDINGS: Registry<CassetteConfig, RecordingBackend> = Registry::new();
+
+/// The cassettes being replayed, one per cassette.
+static REPLAYS: Registry<CassetteConfig, ReplayBackend> = Registry::new();
+
+/// Returns the backend recording or replaying the configured cassette, shared by all
+/// transports using the same cassette at the same time.
+pub(crate) fn backend(config: &Configuration, cassette: &CassetteConfig) -> Result<Arc<dyn HttpBackend>> {
+    let cassette = cassette.clone().redacting_api_key(config);
+    Ok(match cassette.mode {
+        CassetteMode::Record => RECORDINGS.get_or_try_insert(cassette.clone(), || {
+            RecordingBackend::new(ReqwestBackend::shared(config)?, &cassette)
+        })?,
+        CassetteMode::Replay => REPLAYS.get_or_try_insert(cassette.clone(), || ReplayBackend::new(&cassette))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::retry::RetryPolicy;
+    use crate::utils::transport::Transport;
+    use crate::utils::configuration::ApiKey;
+
+    /// A backend answering every request with its method and path, and a session cookie.
+    struct EchoBackend;
+
+    #[async_trait]
+    impl HttpBackend for EchoBackend {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+            let mut response = HttpResponse::new(
+                StatusCode::OK,
+                format!("{{\"echo\": \"{} {}\"}}", request.method, request.url.path()),
+            );
+            response.headers.insert("set-cookie", "session=secret".parse().unwrap());
+            Ok(response)
+        }
+    }
+
+    fn cassette_path(extension: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ga4gh-cassette-{}.{}", uuid::Uuid::new_v4(), extension))
+    }
+
+    fn config() -> Configuration {
+        Configuration::new(Url::parse("https://tes.example.org").unwrap())
+            .with_bearer_access_token("secret-token".to_string())
+            .with_retry_policy(RetryPolicy::none())
+    }
+
+    async fn record(path: &Path) {
+        let cassette = CassetteConfig::new(path, CassetteMode::Record).with_redacted_query("token");
+        let backend = RecordingBackend::new(Arc::new(EchoBackend), &cassette).unwrap();
+        let transport = Transport::with_backend(&config(), Arc::new(backend)).unwrap();
+        transport.get("/tasks", Some(serde_json::json!({"token": "abc", "view": "BASIC"}))).await.unwrap();
+        transport.post("/tasks", Some(serde_json::json!({"name": "hello"}))).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_record_redacts_credentials() {
+        let path = cassette_path("json");
+        record(&path).await;
+
+        let cassette = Cassette::load(&path).unwrap();
+        assert_eq!(cassette.interactions.len(), 2);
+        let first = &cassette.interactions[0];
+        assert_eq!(first.request.url, "https://tes.example.org/tasks?token=REDACTED&view=BASIC");
+        assert_eq!(first.request.headers["authorization"], REDACTED);
+        assert_eq!(first.response.headers["set-cookie"], REDACTED);
+        assert_eq!(first.response.body.body.as_deref(), Some("{\"echo\": \"GET /tasks\"}"));
+        assert_eq!(cassette.interactions[1].request.body.body.as_deref(), Some("{\"name\":\"hello\"}"));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("secret"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_matches_requests() {
+        let path = cassette_path("yaml");
+        record(&path).await;
+
+        let cassette = CassetteConfig::new(&path, CassetteMode::Replay).with_redacted_query("token");
+        let backend = Arc::new(ReplayBackend::new(&cassette).unwrap());
+        let transport = Transport::with_backend(&config(), backend.clone()).unwrap();
+
+        let response = transport.post("/tasks", Some(serde_json::json!({"name": "hello"}))).await.unwrap();
+        assert_eq!(response, "{\"echo\": \"POST /tasks\"}");
+        let params = serde_json::json!({"token": "other", "view": "BASIC"});
+        let response = transport.get("/tasks", Some(params.clone())).await.unwrap();
+        assert_eq!(response, "{\"echo\": \"GET /tasks\"}");
+        assert_eq!(backend.remaining(), 0);
+
+        // each interaction is replayed once, and unknown requests fail
+        let response = transport.get("/tasks", Some(params)).await;
+        assert!(matches!(response, Err(Error::Configuration(_))));
+        let response = transport.post("/tasks", Some(serde_json::json!({"name": "other"}))).await;
+        assert!(matches!(response, Err(Error::Configuration(_))));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recording_written_when_dropped() {
+        let path = cassette_path("yaml");
+        let cassette = CassetteConfig::new(&path, CassetteMode::Record);
+        let backend = RecordingBackend::new(Arc::new(EchoBackend), &cassette).unwrap();
+        let transport = Transport::with_backend(&config(), Arc::new(backend)).unwrap();
+        transport.get("/tasks", None).await.unwrap();
+        assert!(Cassette::load(&path).unwrap().interactions.is_empty());
+
+        drop(transport);
+        assert_eq!(Cassette::load(&path).unwrap().interactions.len(), 1);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_scoped_to_transports_in_use() {
+        let path = cassette_path("yaml");
+        record(&path).await;
+        let cassette = CassetteConfig::new(&path, CassetteMode::Replay).with_redacted_query("token");
+        let config = config().with_cassette(cassette);
+        let post = |transport: &Transport| {
+            let transport = transport.clone();
+            async move { transport.post("/tasks", Some(serde_json::json!({"name": "hello"}))).await }
+        };
+
+        // transports in use at the same time share the cassette
+        let first = Transport::new(&config).unwrap();
+        let second = Transport::new(&config).unwrap();
+        post(&first).await.unwrap();
+        assert!(matches!(post(&second).await, Err(Error::Configuration(_))));
+
+        // a later transport replays it from the start
+        drop((first, second));
+        let later = Transport::new(&config).unwrap();
+        post(&later).await.unwrap();
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_api_key_redacted() {
+        let config = config().with_api_key(
+            ApiKey::new("key".to_string()).with_location(ApiKeyLocation::Header("X-Project-Key".to_string())),
+        );
+        let cassette = CassetteConfig::new("unused.json", CassetteMode::Record).redacting_api_key(&config);
+        assert!(cassette.is_redacted_header("x-project-key"));
+        assert!(cassette.is_redacted_header("Authorization"));
+        assert!(!cassette.is_redacted_header("content-type"));
+    }
+
+    #[test]
+    fn test_binary_body_roundtrip() {
+        let body = RecordedBody::new(&[0xff, 0x00, 0x10]);
+        assert!(body.base64);
+        assert_eq!(body.bytes().unwrap().as_ref(), &[0xff, 0x00, 0x10]);
+    }
+}
@@ -0,0 +1,424 @@
+use crate::utils::backend::{HttpRequest, HttpResponse};
+use crate::utils::middleware::{Middleware, Next};
+use crate::utils::registry::Registry;
+use crate::utils::timeouts::deserialize_millis;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+use url::Url;
+
+/// Settings of the circuit breaker protecting a service.
+///
+/// After `failure_threshold` consecutive failed requests (network errors, timeouts and `5xx`
+/// responses), the breaker opens and requests fail at once with `Error::CircuitOpen`. After
+/// `cool_down`, it lets a single trial request through: the breaker closes again if it
+/// succeeds, and stays open for another `cool_down` if it fails. All transports to the same
+/// base URL with the same settings share one breaker.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::circuit_breaker::CircuitBreakerConfig;
+/// use ga4gh_sdk::utils::configuration::Configuration;
+/// use std::time::Duration;
+///
+/// let breaker = CircuitBreakerConfig::default()
+///     .with_failure_threshold(3)
+///     .with_cool_down(Duration::from_secs(60));
+/// let config = Configuration::new(url::Url::parse("https://tes.example.org").unwrap())
+///     .with_circuit_breaker(breaker);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// The number of consecutive failures that opens the breaker.
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before letting a trial request through.
+    #[serde(rename = "cool_down_ms", deserialize_with = "deserialize_millis")]
+    pub cool_down: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            failure_threshold: 5,
+            cool_down: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Sets the number of consecutive failures that opens the breaker.
+    pub fn with_failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold;
+        self
+    }
+
+    /// Sets how long the breaker stays open before letting a trial request through.
+    pub fn with_cool_down(mut self, cool_down: Duration) -> Self {
+        self.cool_down = cool_down;
+        self
+    }
+}
+
+/// The state of a circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent normally.
+    Closed,
+    /// Requests fail at once until the cool-down is over.
+    Open,
+    /// The cool-down is over and a trial request decides whether the breaker closes.
+    HalfOpen,
+}
+
+/// The health of a service, as seen by its circuit breaker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointHealth {
+    /// The base URL of the service.
+    pub base_url: Url,
+    /// The state of the breaker.
+    pub state: CircuitState,
+    /// The number of consecutive failed requests.
+    pub consecutive_failures: u32,
+    /// While the breaker is open, the time left until a trial request is let through.
+    pub retry_after: Option<Duration>,
+}
+
+impl EndpointHealth {
+    /// Returns whether requests to the service are currently sent.
+    pub fn is_available(&self) -> bool {
+        self.state != CircuitState::Open
+    }
+}
+
+#[derive(Debug)]
+struct Status {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Tracks the failures of a service and stops sending requests to it while it is down.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    base_url: Url,
+    config: CircuitBreakerConfig,
+    status: Mutex<Status>,
+}
+
+/// The breakers in use, one per base URL and configuration.
+static SHARED_BREAKERS: Registry<(Url, CircuitBreakerConfig), CircuitBreaker> = Registry::new();
+
+/// Returns the health of every service watched by the shared circuit breaker of a transport
+/// still in use.
+///
+/// Use this to route work away from services that are currently failing.
+pub fn endpoints_health() -> Vec<EndpointHealth> {
+    SHARED_BREAKERS.values().iter().map(|breaker| breaker.health()).collect()
+}
+
+/// Returns the health of the service at `base_url`, or `None` if no transport to it uses a
+/// circuit breaker.
+///
+/// If several breakers with different settings watch the service, the least healthy one is
+/// returned.
+pub fn endpoint_health(base_url: &Url) -> Option<EndpointHealth> {
+    endpoints_health()
+        .into_iter()
+        .filter(|health| health.base_url == *base_url)
+        .max_by_key(|health| match health.state {
+            CircuitState::Closed => 0,
+            CircuitState::HalfOpen => 1,
+            CircuitState::Open => 2,
+        })
+}
+
+impl CircuitBreaker {
+    /// Creates a closed breaker for the service at `base_url`.
+    pub fn new(base_url: &Url, config: &CircuitBreakerConfig) -> Self {
+        CircuitBreaker {
+            base_url: base_url.clone(),
+            config: config.clone(),
+            status: Mutex::new(Status {
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+            }),
+        }
+    }
+
+    /// Returns the breaker shared by all transports to `base_url` with the same settings. The
+    /// breaker is dropped with the last transport using it.
+    pub fn shared(base_url: &Url, config: &CircuitBreakerConfig) -> Arc<Self> {
+        SHARED_BREAKERS.get_or_insert((base_url.clone(), config.clone()), || CircuitBreaker::new(base_url, config))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Status> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn state(&self, status: &Status) -> CircuitState {
+        match status.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.config.cool_down => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    fn retry_after(&self, status: &Status) -> Duration {
+        status
+            .opened_at
+            .map(|opened_at| self.config.cool_down.saturating_sub(opened_at.elapsed()))
+            .unwrap_or_default()
+    }
+
+    /// Returns the current health of the service.
+    pub fn health(&self) -> EndpointHealth {
+        let status = self.lock();
+        let state = self.state(&status);
+        EndpointHealth {
+            base_url: self.base_url.clone(),
+            state,
+            consecutive_failures: status.consecutive_failures,
+            retry_after: (state == CircuitState::Open).then(|| self.retry_after(&status)),
+        }
+    }
+
+    /// Checks whether a request may be sent.
+    ///
+    /// # Returns
+    ///
+    /// A permit to report the outcome of the request with, or `Error::CircuitOpen` if the
+    /// breaker is open or a trial request is already in flight.
+    pub fn acquire(&self) -> Result<Permit<'_>> {
+        let mut status = self.lock();
+        let trial = match self.state(&status) {
+            CircuitState::Closed => false,
+            CircuitState::HalfOpen if !status.trial_in_flight => {
+                status.trial_in_flight = true;
+                true
+            }
+            CircuitState::Open | CircuitState::HalfOpen => {
+                return Err(Error::CircuitOpen {
+                    base_url: self.base_url.to_string(),
+                    retry_after: self.retry_after(&status),
+                });
+            }
+        };
+        Ok(Permit {
+            breaker: self,
+            trial,
+            reported: false,
+        })
+    }
+}
+
+/// Permission to send one request, given by `CircuitBreaker::acquire`.
+///
+/// Dropping the permit without reporting an outcome, e.g. when the request is cancelled,
+/// counts neither as a success nor as a failure.
+#[derive(Debug)]
+pub struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    trial: bool,
+    reported: bool,
+}
+
+impl Permit<'_> {
+    /// Reports a successful request, closing the breaker.
+    pub fn success(mut self) {
+        self.reported = true;
+        let mut status = self.breaker.lock();
+        status.consecutive_failures = 0;
+        status.opened_at = None;
+        status.trial_in_flight = false;
+    }
+
+    /// Reports a failed request, opening the breaker after too many consecutive failures.
+    pub fn failure(mut self) {
+        self.reported = true;
+        let mut status = self.breaker.lock();
+        status.consecutive_failures = status.consecutive_failures.saturating_add(1);
+        let was_open = status.opened_at.is_some();
+        if self.trial || status.consecutive_failures >= self.breaker.config.failure_threshold {
+            status.opened_at = Some(Instant::now());
+            if !was_open {
+                warn!(
+                    "{} failed {} times in a row, not sending requests for {:?}",
+                    self.breaker.base_url,
+                    status.consecutive_failures,
+                    self.breaker.config.cool_down
+                );
+            }
+        }
+        status.trial_in_flight = false;
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if self.trial && !self.reported {
+            self.breaker.lock().trial_in_flight = false;
+        }
+    }
+}
+
+/// Returns whether the outcome of a request counts as a failure of the service.
+fn is_failure(result: &Result<HttpResponse>) -> bool {
+    match result {
+        Ok(response) => response.status.is_server_error(),
+        Err(Error::Transport(_) | Error::Timeout) => true,
+        Err(_) => false,
+    }
+}
+
+/// Fails requests fast while the service's `CircuitBreaker` is open.
+///
+/// Part of the default chain when the configuration sets a circuit breaker, after the retry
+/// middleware so every attempt counts, and before the rate limits so rejected requests do
+/// not use up the budget. `Error::CircuitOpen` is never retried.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerMiddleware {
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl CircuitBreakerMiddleware {
+    /// Creates a middleware guarding requests with the given breaker.
+    pub fn new(breaker: Arc<CircuitBreaker>) -> Self {
+        CircuitBreakerMiddleware { breaker }
+    }
+}
+
+#[async_trait]
+impl Middleware for CircuitBreakerMiddleware {
+    async fn handle(&self, request: HttpRequest, next: Next<'_>) -> Result<HttpResponse> {
+        let permit = self.breaker.acquire()?;
+        let result = next.run(request).await;
+        if is_failure(&result) {
+            permit.failure();
+        } else {
+            permit.success();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::backend::{HttpBackend, StatusCode};
+    use crate::utils::configuration::Configuration;
+    use crate::utils::retry::RetryPolicy;
+    use crate::utils::transport::Transport;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// A backend failing with `503` while `down` is set, counting the requests it receives.
+    #[derive(Default)]
+    struct FlakyBackend {
+        down: AtomicBool,
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpBackend for FlakyBackend {
+        async fn execute(&self, _request: HttpRequest) -> Result<HttpResponse> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            if self.down.load(Ordering::SeqCst) {
+                Ok(HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE, "down"))
+            } else {
+                Ok(HttpResponse::new(StatusCode::OK, "up"))
+            }
+        }
+    }
+
+    fn transport(base_url: &str, backend: Arc<FlakyBackend>) -> Transport {
+        let breaker = CircuitBreakerConfig::default()
+            .with_failure_threshold(2)
+            .with_cool_down(Duration::from_millis(100));
+        let config = Configuration::new(Url::parse(base_url).unwrap())
+            .with_retry_policy(RetryPolicy::none())
+            .with_circuit_breaker(breaker);
+        Transport::with_backend(&config, backend).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_breaker_opens_and_recovers() {
+        let base_url = Url::parse("https://breaker.example.org").unwrap();
+        let backend = Arc::new(FlakyBackend::default());
+        let transport = transport(base_url.as_str(), backend.clone());
+        backend.down.store(true, Ordering::SeqCst);
+
+        for _ in 0..2 {
+            assert!(matches!(transport.get("/tasks", None).await, Err(Error::Http { .. })));
+        }
+        // open: fails fast without reaching the service
+        let response = transport.get("/tasks", None).await;
+        assert!(matches!(response, Err(Error::CircuitOpen { .. })));
+        assert_eq!(backend.requests.load(Ordering::SeqCst), 2);
+        let health = endpoint_health(&base_url).unwrap();
+        assert_eq!(health.state, CircuitState::Open);
+        assert!(!health.is_available());
+
+        // half-open: a failed trial opens the breaker again
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(endpoint_health(&base_url).unwrap().state, CircuitState::HalfOpen);
+        assert!(matches!(transport.get("/tasks", None).await, Err(Error::Http { .. })));
+        assert!(matches!(transport.get("/tasks", None).await, Err(Error::CircuitOpen { .. })));
+
+        // a successful trial closes it
+        backend.down.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(transport.get("/tasks", None).await.unwrap(), "up");
+        let health = endpoint_health(&base_url).unwrap();
+        assert_eq!(health.state, CircuitState::Closed);
+        assert_eq!(health.consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_shared_per_base_url() {
+        let backend = Arc::new(FlakyBackend::default());
+        backend.down.store(true, Ordering::SeqCst);
+        let first = transport("https://shared-breaker.example.org", backend.clone());
+        let second = transport("https://shared-breaker.example.org", backend.clone());
+        let other = transport("https://other-breaker.example.org", backend.clone());
+
+        first.get("/tasks", None).await.unwrap_err();
+        second.get("/tasks", None).await.unwrap_err();
+        assert!(matches!(first.get("/tasks", None).await, Err(Error::CircuitOpen { .. })));
+        assert!(matches!(other.get("/tasks", None).await, Err(Error::Http { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_health_lists_breakers_in_use() {
+        let base_url = Url::parse("https://dropped-breaker.example.org").unwrap();
+        let transport = transport(base_url.as_str(), Arc::new(FlakyBackend::default()));
+        let clone = transport.clone();
+        assert_eq!(endpoint_health(&base_url).unwrap().state, CircuitState::Closed);
+
+        drop(transport);
+        assert!(endpoint_health(&base_url).is_some());
+        drop(clone);
+        assert!(endpoint_health(&base_url).is_none());
+        assert!(endpoints_health().iter().all(|health| health.base_url != base_url));
+    }
+
+    #[test]
+    fn test_client_errors_are_not_failures() {
+        assert!(!is_failure(&Ok(HttpResponse::new(StatusCode::NOT_FOUND, ""))));
+        assert!(is_failure(&Ok(HttpResponse::new(StatusCode::BAD_GATEWAY, ""))));
+        assert!(is_failure(&Err(Error::Timeout)));
+        assert!(!is_failure(&Err(Error::InvalidInput("bad".to_string()))));
+    }
+
+    #[test]
+    fn test_deserialize_circuit_breaker() {
+        let config: CircuitBreakerConfig =
+            serde_json::from_value(serde_json::json!({ "failure_threshold": 3, "cool_down_ms": 1000 })).unwrap();
+
+        assert_eq!(config.failure_threshold, 3);
+        assert_eq!(config.cool_down, Duration::from_secs(1));
+    }
+}
@@ -1,15 +1,33 @@
 use url::Url;
 use serde_json::Value;
 use crate::clients::ServiceType;
-use log::warn;
+use crate::utils::cache::CacheConfig;
+use crate::utils::cassette::CassetteConfig;
+use crate::utils::circuit_breaker::CircuitBreakerConfig;
+use crate::utils::pool::PoolConfig;
+use crate::utils::proxy::ProxyConfig;
+use crate::utils::rate_limit::RateLimitConfig;
+use crate::utils::retry::RetryPolicy;
+use crate::utils::staging::StagingConfig;
+use crate::utils::timeouts::TimeoutConfig;
+use crate::utils::tls::TlsConfig;
+use crate::{Error, Result};
+use tracing::warn;
 /// A struct representing a configuration for the SDK.
 ///
 /// The `Configuration` struct is responsible for specifying details of the Endpoint where the requests are made.
 /// It provides methods for making constructing new configuration, changing the base url, and specifying a default configuration.
+///
+/// If several credentials are set, only one of them is sent with each request, in the order
+/// `bearer_access_token`, `oauth_access_token`, `basic_auth`, `api_key`
+/// (see [`Authentication`](crate::utils::auth::Authentication)).
 #[derive(Debug, Clone)]
 pub struct Configuration {
     /// The base path for API requests.
     pub base_path: Url,
+    /// An extra path between the base path and the path of the API in its specification,
+    /// for services deployed below a prefix.
+    pub path_prefix: Option<String>,
     /// The user agent to be used in API requests.
     pub user_agent: Option<String>,
     /// The basic authentication credentials.
@@ -20,6 +38,26 @@ pub struct Configuration {
     pub bearer_access_token: Option<String>,
     /// The API key for authentication.
     pub api_key: Option<ApiKey>,
+    /// The policy for retrying failed requests.
+    pub retry_policy: RetryPolicy,
+    /// The TLS settings for the connections to the service.
+    pub tls: TlsConfig,
+    /// The proxy settings for the connections to the service.
+    pub proxy: ProxyConfig,
+    /// The timeouts applied to every request.
+    pub timeouts: TimeoutConfig,
+    /// The connection pool settings.
+    pub pool: PoolConfig,
+    /// The client-side rate limits for the requests to the service.
+    pub rate_limit: RateLimitConfig,
+    /// The response cache for `GET` requests.
+    pub cache: CacheConfig,
+    /// The cassette the traffic is recorded to or replayed from, if any.
+    pub cassette: Option<CassetteConfig>,
+    /// The circuit breaker stopping requests while the service is failing, if any.
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// How local task inputs are staged before a TES task is created, if at all.
+    pub staging: Option<StagingConfig>,
 }
 
 /// Represents the basic authentication credentials.
@@ -38,6 +76,54 @@ pub struct ApiKey {
     pub prefix: Option<String>,
     /// The key value of the API key.
     pub key: String,
+    /// Where the API key is sent in the request.
+    pub location: ApiKeyLocation,
+}
+
+/// Represents where an API key is placed in a request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiKeyLocation {
+    /// Sent in the header with the given name.
+    Header(String),
+    /// Sent in the query parameter with the given name.
+    Query(String),
+}
+
+impl Default for ApiKeyLocation {
+    fn default() -> Self {
+        ApiKeyLocation::Header("X-API-Key".to_owned())
+    }
+}
+
+impl ApiKey {
+    /// Creates a new API key sent in the `X-API-Key` header without a prefix.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key value of the API key.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of ApiKey.
+    pub fn new(key: String) -> Self {
+        ApiKey {
+            prefix: None,
+            key,
+            location: ApiKeyLocation::default(),
+        }
+    }
+
+    /// Sets the prefix sent in front of the key, e.g. `Token`.
+    pub fn with_prefix(mut self, prefix: String) -> Self {
+        self.prefix = Some(prefix);
+        self
+    }
+
+    /// Sets where the API key is placed in the request.
+    pub fn with_location(mut self, location: ApiKeyLocation) -> Self {
+        self.location = location;
+        self
+    }
 }
 
 impl Configuration {
@@ -58,11 +144,22 @@ impl Configuration {
     ) -> Self {
         Configuration {
             base_path,
+            path_prefix: None,
             user_agent: Some("GA4GH SDK".to_owned()),
             basic_auth: None,
             oauth_access_token: None,
             bearer_access_token: None,
             api_key: None,
+            retry_policy: RetryPolicy::default(),
+            tls: TlsConfig::default(),
+            proxy: ProxyConfig::default(),
+            timeouts: TimeoutConfig::default(),
+            pool: PoolConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            cache: CacheConfig::default(),
+            cassette: None,
+            circuit_breaker: None,
+            staging: None,
         }
     }
 
@@ -80,6 +177,20 @@ impl Configuration {
         self
     }
     
+    /// Sets the extra path between the base path and the path of the API in its specification.
+    ///
+    /// # Arguments
+    ///
+    /// * `path_prefix` - The deployment prefix, e.g. `/prod`.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_path_prefix(mut self, path_prefix: String) -> Self {
+        self.path_prefix = Some(path_prefix);
+        self
+    }
+
     /// Sets the user agent for API requests.
     ///
     /// # Arguments
@@ -122,6 +233,174 @@ impl Configuration {
         self
     }
 
+    /// Sets the bearer access token for API requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `bearer_access_token` - The bearer access token for authentication.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_bearer_access_token(mut self, bearer_access_token: String) -> Self {
+        self.bearer_access_token = Some(bearer_access_token);
+        self
+    }
+
+    /// Sets the API key for API requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - The API key for authentication.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_api_key(mut self, api_key: ApiKey) -> Self {
+        self.api_key = Some(api_key);
+        self
+    }
+
+    /// Sets the policy for retrying failed requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry_policy` - The retry policy, or `RetryPolicy::none()` to disable retries.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets the TLS settings for the connections to the service.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls` - The TLS settings, e.g. extra root certificates or a client certificate.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Sets the proxy settings for the connections to the service.
+    ///
+    /// # Arguments
+    ///
+    /// * `proxy` - The proxy settings, or `ProxyConfig::none()` to ignore the environment.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Sets the timeouts applied to every request.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeouts` - The connect, read and request timeouts.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_timeouts(mut self, timeouts: TimeoutConfig) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Sets the connection pool settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - The idle timeout, idle connections per host and HTTP version.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_pool(mut self, pool: PoolConfig) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// Sets the client-side rate limits for the requests to the service.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate_limit` - The budgets for read and write requests.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Sets the response cache for `GET` requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - The cache settings, e.g. `CacheConfig::enabled()`.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_cache(mut self, cache: CacheConfig) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Records the traffic to a cassette file, or replays it from one instead of the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `cassette` - The cassette file, the mode and the values to redact.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_cassette(mut self, cassette: CassetteConfig) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Sets the circuit breaker stopping requests while the service is failing.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_breaker` - The failure threshold and cool-down of the breaker.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Sets how local task inputs are staged before a TES task is created.
+    ///
+    /// # Arguments
+    ///
+    /// * `staging` - Where local inputs are uploaded and which ones are inlined.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of Configuration.
+    pub fn with_staging(mut self, staging: StagingConfig) -> Self {
+        self.staging = Some(staging);
+        self
+    }
+
     /// Loads the configuration from a JSON file.
     ///
     /// # Arguments
@@ -131,24 +410,34 @@ impl Configuration {
     /// # Errors
     ///
     /// This function will return an error if the configuration file is missing or malformed.
-
-    pub fn from_file(service_type: ServiceType)-> Result<Self, Box<dyn std::error::Error>> {
-        let config_file_path = dirs::home_dir().ok_or("Home directory not found")?.join(".ga4gh-cli/config.json");
+    pub fn from_file(service_type: ServiceType) -> Result<Self> {
+        let config_file_path = dirs::home_dir()
+            .ok_or_else(|| Error::Configuration("Home directory not found".to_string()))?
+            .join(".ga4gh-cli/config.json");
         if config_file_path.exists() {
             let contents = std::fs::read_to_string(config_file_path)?;
-            let config_json: Value = serde_json::from_str(&contents)?;
+            let config_json: Value = serde_json::from_str(&contents)
+                .map_err(|e| Error::Configuration(format!("Configuration file is not valid JSON: {}", e)))?;
             if !config_json.is_object() {
-                return Err("Configuration file must be a JSON object".into());
+                return Err(Error::Configuration("Configuration file must be a JSON object".to_string()));
             }
             if !config_json[service_type.as_str()].is_object() {
-                return Err("Configuration file must contain the requested `{service_type}` configuration".into());
+                return Err(Error::Configuration(format!(
+                    "Configuration file must contain the requested `{}` configuration",
+                    service_type.as_str()
+                )));
             }
             let config_json = config_json[service_type.as_str()].as_object().unwrap();
             if !config_json["base_path"].is_string() {
-                return Err("Configuration file must contain a 'base_path' string".into());
+                return Err(Error::Configuration("Configuration file must contain a 'base_path' string".to_string()));
             }
-            let base_path = Url::parse(config_json["base_path"].as_str().unwrap_or_default())?;
+            let base_path = Url::parse(config_json["base_path"].as_str().unwrap_or_default())
+                .map_err(|e| Error::Configuration(format!("Invalid 'base_path': {}", e)))?;
             let mut config = Configuration::new(base_path);
+            if config_json["path_prefix"].is_string() {
+                let path_prefix = config_json["path_prefix"].as_str().unwrap_or_default().to_string();
+                config = config.with_path_prefix(path_prefix);
+            }
             if config_json["basic_auth"].is_object() {
                 let basic_auth = BasicAuth {
                     username: config_json["basic_auth"]["username"].as_str().unwrap_or_default().to_string(),
@@ -160,6 +449,73 @@ impl Configuration {
                 let oauth_access_token = config_json["oauth_access_token"].as_str().unwrap_or_default().to_string();
                 config = config.with_oauth_access_token(oauth_access_token);
             }
+            if config_json["bearer_access_token"].is_string() {
+                let bearer_access_token = config_json["bearer_access_token"].as_str().unwrap_or_default().to_string();
+                config = config.with_bearer_access_token(bearer_access_token);
+            }
+            if config_json["api_key"].is_object() {
+                let api_key_json = &config_json["api_key"];
+                if !api_key_json["key"].is_string() {
+                    return Err(Error::Configuration("Configuration file must contain an 'api_key.key' string".to_string()));
+                }
+                let mut api_key = ApiKey::new(api_key_json["key"].as_str().unwrap_or_default().to_string());
+                if let Some(prefix) = api_key_json["prefix"].as_str() {
+                    api_key = api_key.with_prefix(prefix.to_string());
+                }
+                if let Some(header) = api_key_json["header"].as_str() {
+                    api_key = api_key.with_location(ApiKeyLocation::Header(header.to_string()));
+                } else if let Some(query) = api_key_json["query"].as_str() {
+                    api_key = api_key.with_location(ApiKeyLocation::Query(query.to_string()));
+                }
+                config = config.with_api_key(api_key);
+            }
+            if config_json["retry"].is_object() {
+                let retry_policy: RetryPolicy = serde_json::from_value(config_json["retry"].clone())
+                    .map_err(|e| Error::Configuration(format!("Invalid 'retry' configuration: {}", e)))?;
+                config = config.with_retry_policy(retry_policy);
+            }
+            if config_json["tls"].is_object() {
+                config = config.with_tls(TlsConfig::from_json(&config_json["tls"])?);
+            }
+            if config_json["proxy"].is_object() {
+                config = config.with_proxy(ProxyConfig::from_json(&config_json["proxy"])?);
+            }
+            if config_json["timeouts"].is_object() {
+                let timeouts: TimeoutConfig = serde_json::from_value(config_json["timeouts"].clone())
+                    .map_err(|e| Error::Configuration(format!("Invalid 'timeouts' configuration: {}", e)))?;
+                config = config.with_timeouts(timeouts);
+            }
+            if config_json["pool"].is_object() {
+                let pool: PoolConfig = serde_json::from_value(config_json["pool"].clone())
+                    .map_err(|e| Error::Configuration(format!("Invalid 'pool' configuration: {}", e)))?;
+                config = config.with_pool(pool);
+            }
+            if config_json["rate_limit"].is_object() {
+                let rate_limit: RateLimitConfig = serde_json::from_value(config_json["rate_limit"].clone())
+                    .map_err(|e| Error::Configuration(format!("Invalid 'rate_limit' configuration: {}", e)))?;
+                rate_limit.validate()?;
+                config = config.with_rate_limit(rate_limit);
+            }
+            if config_json["cache"].is_object() {
+                let cache: CacheConfig = serde_json::from_value(config_json["cache"].clone())
+                    .map_err(|e| Error::Configuration(format!("Invalid 'cache' configuration: {}", e)))?;
+                config = config.with_cache(cache);
+            }
+            if config_json["cassette"].is_object() {
+                let cassette: CassetteConfig = serde_json::from_value(config_json["cassette"].clone())
+                    .map_err(|e| Error::Configuration(format!("Invalid 'cassette' configuration: {}", e)))?;
+                config = config.with_cassette(cassette);
+            }
+            if config_json["circuit_breaker"].is_object() {
+                let circuit_breaker: CircuitBreakerConfig = serde_json::from_value(config_json["circuit_breaker"].clone())
+                    .map_err(|e| Error::Configuration(format!("Invalid 'circuit_breaker' configuration: {}", e)))?;
+                config = config.with_circuit_breaker(circuit_breaker);
+            }
+            if config_json["staging"].is_object() {
+                let staging: StagingConfig = serde_json::from_value(config_json["staging"].clone())
+                    .map_err(|e| Error::Configuration(format!("Invalid 'staging' configuration: {}", e)))?;
+                config = config.with_staging(staging);
+            }
             return Ok(config);
         }
         warn!("Configuration file not found at {:?}, using empy defualt configuration", config_file_path);
@@ -177,11 +533,22 @@ impl Default for Configuration {
     fn default() -> Self {
         Configuration {
             base_path: Url::parse("http://localhost").unwrap(),
+            path_prefix: None,
             user_agent: Some("GA4GH SDK".to_owned()),
             basic_auth: None,
             oauth_access_token: None,
             bearer_access_token: None,
             api_key: None,
+            retry_policy: RetryPolicy::default(),
+            tls: TlsConfig::default(),
+            proxy: ProxyConfig::default(),
+            timeouts: TimeoutConfig::default(),
+            pool: PoolConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            cache: CacheConfig::default(),
+            cassette: None,
+            circuit_breaker: None,
+            staging: None,
         }
     }
 }
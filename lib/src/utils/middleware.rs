@@ -0,0 +1,287 @@
+use crate::utils::auth::Authentication;
+use crate::utils::backend::{HeaderMap, HttpBackend, HttpRequest, HttpResponse};
+use crate::utils::body::Body;
+use crate::utils::retry::RetryPolicy;
+use crate::Result;
+use async_trait::async_trait;
+use tracing::warn;
+use std::sync::Arc;
+
+/// A hook run around every HTTP request sent by a `Transport`.
+///
+/// Middlewares form an ordered chain: the first middleware receives the request first and
+/// the response last. Each middleware can change the outgoing request, decide whether and
+/// how often to call the rest of the chain through `next`, and inspect or replace the
+/// response or error it returns. Responses with a non-success status are passed back as
+/// `Ok(HttpResponse)`; the `Transport` turns them into `Error::Http` after the chain.
+///
+/// # Examples
+///
+/// ```
+/// use async_trait::async_trait;
+/// use ga4gh_sdk::utils::backend::{HttpRequest, HttpResponse};
+/// use ga4gh_sdk::utils::configuration::Configuration;
+/// use ga4gh_sdk::utils::middleware::{Middleware, Next};
+/// use ga4gh_sdk::utils::transport::Transport;
+///
+/// struct AuditLog;
+///
+/// #[async_trait]
+/// impl Middleware for AuditLog {
+///     async fn handle(&self, request: HttpRequest, next: Next<'_>) -> ga4gh_sdk::Result<HttpResponse> {
+///         let (method, url) = (request.method.clone(), request.url.clone());
+///         let result = next.run(request).await;
+///         match &result {
+///             Ok(response) => tracing::info!("{} {} -> {}", method, url, response.status),
+///             Err(e) => tracing::warn!("{} {} failed: {}", method, url, e),
+///         }
+///         result
+///     }
+/// }
+///
+/// let config = Configuration::new(url::Url::parse("https://api.example.com").unwrap());
+/// let transport = Transport::new(&config).unwrap().with_middleware(AuditLog);
+/// ```
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Handles a request, usually by passing it on to `next`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The outgoing request.
+    /// * `next` - The rest of the chain, ending with the `HttpBackend`.
+    ///
+    /// # Returns
+    ///
+    /// The response, or an error if no response was received.
+    async fn handle(&self, request: HttpRequest, next: Next<'_>) -> Result<HttpResponse>;
+}
+
+/// The remaining part of a middleware chain.
+///
+/// `Next` is `Copy`, so a middleware can run the rest of the chain several times, e.g. to
+/// retry a request.
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    backend: &'a dyn HttpBackend,
+    middlewares: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    /// Creates the start of a chain running `middlewares` in order, then `backend`.
+    pub(crate) fn new(backend: &'a dyn HttpBackend, middlewares: &'a [Arc<dyn Middleware>]) -> Self {
+        Next { backend, middlewares }
+    }
+
+    /// Runs the rest of the chain with the given request.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request to pass on.
+    ///
+    /// # Returns
+    ///
+    /// The response returned by the rest of the chain.
+    pub async fn run(self, request: HttpRequest) -> Result<HttpResponse> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => {
+                middleware
+                    .handle(request, Next::new(self.backend, rest))
+                    .await
+            }
+            None => self.backend.execute(request).await,
+        }
+    }
+}
+
+/// Applies a credential to every request.
+///
+/// Part of the default chain when the configuration holds a credential.
+#[derive(Debug, Clone)]
+pub struct AuthMiddleware {
+    auth: Authentication,
+}
+
+impl AuthMiddleware {
+    /// Creates a middleware applying the given credential.
+    pub fn new(auth: Authentication) -> Self {
+        AuthMiddleware { auth }
+    }
+}
+
+#[async_trait]
+impl Middleware for AuthMiddleware {
+    async fn handle(&self, mut request: HttpRequest, next: Next<'_>) -> Result<HttpResponse> {
+        self.auth.apply(&mut request)?;
+        next.run(request).await
+    }
+}
+
+/// Retries failed requests according to a `RetryPolicy`.
+///
+/// Part of the default chain. Each retry runs the rest of the chain again. Requests with a
+/// streamed body are sent only once. For requests with `buffer_response` set, the response
+/// body is read within each attempt, so a connection reset while reading it is retried too.
+#[derive(Debug, Clone)]
+pub struct RetryMiddleware {
+    policy: RetryPolicy,
+}
+
+impl RetryMiddleware {
+    /// Creates a middleware retrying requests according to the given policy.
+    pub fn new(policy: RetryPolicy) -> Self {
+        RetryMiddleware { policy }
+    }
+}
+
+#[async_trait]
+impl Middleware for RetryMiddleware {
+    async fn handle(&self, request: HttpRequest, next: Next<'_>) -> Result<HttpResponse> {
+        let mut attempt = 1;
+        loop {
+            let Some(attempt_request) = request.try_clone() else {
+                return next.run(request).await;
+            };
+            let result = match next.run(attempt_request).await {
+                Ok(response) if request.buffer_response => buffer(response).await,
+                result => result,
+            };
+            match self.policy.retry_delay(&request.method, attempt, &result) {
+                Some(delay) => {
+                    let reason = match result {
+                        Ok(ref response) => response.status.to_string(),
+                        Err(ref e) => e.to_string(),
+                    };
+                    warn!(
+                        "{} {} failed (attempt {}/{}): {}. Retrying in {:?}",
+                        request.method, request.url, attempt, self.policy.max_attempts, reason, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                None => return result,
+            }
+        }
+    }
+}
+
+/// Reads the whole body of a response, failing if the connection breaks while reading it.
+async fn buffer(response: HttpResponse) -> Result<HttpResponse> {
+    let body = response.body.collect().await?;
+    Ok(HttpResponse {
+        status: response.status,
+        headers: response.headers,
+        body: Body::from(body),
+    })
+}
+
+/// Adds a fixed set of headers, such as a project ID, to every request.
+///
+/// Headers already present on the request are overwritten.
+#[derive(Debug, Clone)]
+pub struct HeadersMiddleware {
+    headers: HeaderMap,
+}
+
+impl HeadersMiddleware {
+    /// Creates a middleware adding the given headers.
+    pub fn new(headers: HeaderMap) -> Self {
+        HeadersMiddleware { headers }
+    }
+}
+
+#[async_trait]
+impl Middleware for HeadersMiddleware {
+    async fn handle(&self, mut request: HttpRequest, next: Next<'_>) -> Result<HttpResponse> {
+        for (name, value) in &self.headers {
+            request.headers.insert(name.clone(), value.clone());
+        }
+        next.run(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::backend::StatusCode;
+    use crate::utils::configuration::Configuration;
+    use crate::utils::transport::Transport;
+    use std::sync::Mutex;
+    use url::Url;
+
+    /// A backend answering every request with the value of its `x-trace` header.
+    struct EchoBackend;
+
+    #[async_trait]
+    impl HttpBackend for EchoBackend {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+            let trace = request
+                .headers
+                .get("x-trace")
+                .map(|v| v.to_str().unwrap().to_string())
+                .unwrap_or_default();
+            let authorization = request.headers.contains_key("authorization");
+            Ok(HttpResponse::new(StatusCode::OK, format!("{}|{}", trace, authorization)))
+        }
+    }
+
+    /// Appends its name to the `x-trace` header and records the order it saw the response in.
+    struct Tracer {
+        name: &'static str,
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Middleware for Tracer {
+        async fn handle(&self, mut request: HttpRequest, next: Next<'_>) -> Result<HttpResponse> {
+            let trace = match request.headers.get("x-trace") {
+                Some(value) => format!("{},{}", value.to_str().unwrap(), self.name),
+                None => self.name.to_string(),
+            };
+            request.headers.insert("x-trace", trace.parse().unwrap());
+            let response = next.run(request).await;
+            self.seen.lock().unwrap().push(self.name.to_string());
+            response
+        }
+    }
+
+    fn config() -> Configuration {
+        Configuration::new(Url::parse("https://api.example.com").unwrap())
+            .with_bearer_access_token("my_token".to_owned())
+    }
+
+    #[tokio::test]
+    async fn test_middlewares_run_in_order() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let transport = Transport::with_backend(&config(), Arc::new(EchoBackend)).unwrap()
+            .with_middleware(Tracer { name: "first", seen: seen.clone() })
+            .with_middleware(Tracer { name: "second", seen: seen.clone() });
+
+        let response = transport.get("/test", None).await.unwrap();
+
+        assert_eq!(response, "first,second|true");
+        assert_eq!(*seen.lock().unwrap(), vec!["second", "first"]);
+    }
+
+    #[tokio::test]
+    async fn test_replace_default_middlewares() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let transport = Transport::with_backend(&config(), Arc::new(EchoBackend)).unwrap()
+            .with_middlewares(vec![Arc::new(Tracer { name: "only", seen })]);
+
+        // the built-in auth middleware was removed from the chain
+        let response = transport.get("/test", None).await.unwrap();
+        assert_eq!(response, "only|false");
+    }
+
+    #[tokio::test]
+    async fn test_headers_middleware() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-trace", "project-42".parse().unwrap());
+        let transport = Transport::with_backend(&config(), Arc::new(EchoBackend)).unwrap()
+            .with_middleware(HeadersMiddleware::new(headers));
+
+        let response = transport.get("/test", None).await.unwrap();
+        assert_eq!(response, "project-42|true");
+    }
+}
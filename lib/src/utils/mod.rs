@@ -1,3 +1,20 @@
+pub mod auth;
+pub mod backend;
+pub mod body;
+pub mod cache;
+pub mod cassette;
+pub mod circuit_breaker;
+pub mod middleware;
 pub mod configuration;
+pub mod pool;
+pub mod proxy;
+pub mod rate_limit;
+pub(crate) mod registry;
+pub mod retry;
+pub mod staging;
+pub mod streaming;
+pub mod telemetry;
+pub mod timeouts;
+pub mod tls;
 pub mod transport;
 pub mod test_utils;
\ No newline at end of file
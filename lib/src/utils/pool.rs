@@ -0,0 +1,111 @@
+use crate::utils::timeouts::deserialize_optional_millis;
+use std::time::Duration;
+
+/// The HTTP version used to talk to a service.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpVersion {
+    /// HTTP/2 when the server offers it during the TLS handshake, HTTP/1.1 otherwise.
+    #[default]
+    Auto,
+    /// HTTP/1.1 only.
+    Http1,
+    /// HTTP/2 only, also over plain HTTP (prior knowledge).
+    Http2,
+}
+
+/// Connection pool settings of a `Transport`.
+///
+/// Transports built from configurations with the same connection settings (pool, timeouts,
+/// TLS and proxy) share one pool, so connections are reused across all clients.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::configuration::Configuration;
+/// use ga4gh_sdk::utils::pool::{HttpVersion, PoolConfig};
+/// use std::time::Duration;
+///
+/// let pool = PoolConfig::default()
+///     .with_idle_timeout(Duration::from_secs(30))
+///     .with_max_idle_per_host(4)
+///     .with_http_version(HttpVersion::Http1);
+/// let config = Configuration::new(url::Url::parse("https://api.example.com").unwrap())
+///     .with_pool(pool);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PoolConfig {
+    /// How long an idle connection is kept open. `None` keeps it open indefinitely.
+    #[serde(rename = "idle_timeout_ms", deserialize_with = "deserialize_optional_millis")]
+    pub idle_timeout: Option<Duration>,
+    /// The maximum number of idle connections kept per host.
+    pub max_idle_per_host: usize,
+    /// The HTTP version used to talk to the service.
+    pub http_version: HttpVersion,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            idle_timeout: Some(Duration::from_secs(90)),
+            max_idle_per_host: usize::MAX,
+            http_version: HttpVersion::Auto,
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Sets how long an idle connection is kept open.
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept per host.
+    pub fn with_max_idle_per_host(mut self, max_idle_per_host: usize) -> Self {
+        self.max_idle_per_host = max_idle_per_host;
+        self
+    }
+
+    /// Sets the HTTP version used to talk to the service.
+    pub fn with_http_version(mut self, http_version: HttpVersion) -> Self {
+        self.http_version = http_version;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::configuration::Configuration;
+    use crate::utils::transport::Transport;
+    use std::sync::Arc;
+    use url::Url;
+
+    #[test]
+    fn test_transports_share_pool() {
+        let config = Configuration::new(Url::parse("https://api.example.com").unwrap())
+            .with_pool(PoolConfig::default().with_max_idle_per_host(3));
+        let first = Transport::new(&config).unwrap();
+        let second = Transport::new(&config.clone()).unwrap();
+        assert!(Arc::ptr_eq(first.backend(), second.backend()));
+
+        let other = config.with_pool(PoolConfig::default().with_max_idle_per_host(5));
+        let third = Transport::new(&other).unwrap();
+        assert!(!Arc::ptr_eq(first.backend(), third.backend()));
+    }
+
+    #[test]
+    fn test_deserialize_pool() {
+        let pool: PoolConfig = serde_json::from_value(serde_json::json!({
+            "max_idle_per_host": 2,
+            "http_version": "http2"
+        }))
+        .unwrap();
+
+        assert_eq!(pool.idle_timeout, PoolConfig::default().idle_timeout);
+        assert_eq!(pool.max_idle_per_host, 2);
+        assert_eq!(pool.http_version, HttpVersion::Http2);
+    }
+}
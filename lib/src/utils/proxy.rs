@@ -0,0 +1,310 @@
+use crate::{Error, Result};
+use reqwest::{ClientBuilder, Proxy};
+use serde_json::Value;
+use std::net::IpAddr;
+use url::Url;
+
+/// HTTP(S) proxy settings for the connections to a service.
+///
+/// Without an explicit proxy `url`, the standard `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and
+/// `NO_PROXY` environment variables are honoured unless `use_env` is disabled. An explicit
+/// proxy is used for all requests except those to hosts in `no_proxy`; when `no_proxy` is
+/// empty, the `NO_PROXY` environment variable is used instead (again unless `use_env` is
+/// disabled).
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::configuration::Configuration;
+/// use ga4gh_sdk::utils::proxy::ProxyConfig;
+///
+/// let proxy = ProxyConfig::new(url::Url::parse("http://proxy.hpc.example.org:3128").unwrap())
+///     .with_credentials("me".to_string(), Some("secret".to_string()))
+///     .with_no_proxy(vec!["localhost".to_string(), ".internal.example.org".to_string()]);
+/// let config = Configuration::new(url::Url::parse("https://tes.example.org").unwrap())
+///     .with_proxy(proxy);
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct ProxyConfig {
+    /// The URL of the proxy, e.g. `http://proxy:3128`. `None` falls back to the environment.
+    pub url: Option<Url>,
+    /// The username for the proxy.
+    pub username: Option<String>,
+    /// The password for the proxy.
+    pub password: Option<String>,
+    /// Hosts, domains (`.example.org`), IP addresses or CIDR ranges reached without the proxy.
+    pub no_proxy: Vec<String>,
+    /// Whether the proxy environment variables are honoured.
+    pub use_env: bool,
+}
+
+impl std::fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("url", &self.url.as_ref().map(Url::as_str))
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("no_proxy", &self.no_proxy)
+            .field("use_env", &self.use_env)
+            .finish()
+    }
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        ProxyConfig {
+            url: None,
+            username: None,
+            password: None,
+            no_proxy: Vec::new(),
+            use_env: true,
+        }
+    }
+}
+
+impl ProxyConfig {
+    /// Creates settings sending all requests through the given proxy.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the proxy.
+    ///
+    /// # Returns
+    ///
+    /// A new `ProxyConfig` instance.
+    pub fn new(url: Url) -> Self {
+        ProxyConfig {
+            url: Some(url),
+            ..ProxyConfig::default()
+        }
+    }
+
+    /// Creates settings that never use a proxy, whatever the environment says.
+    pub fn none() -> Self {
+        ProxyConfig {
+            use_env: false,
+            ..ProxyConfig::default()
+        }
+    }
+
+    /// Sets the credentials sent to the proxy with basic authentication.
+    pub fn with_credentials(mut self, username: String, password: Option<String>) -> Self {
+        self.username = Some(username);
+        self.password = password;
+        self
+    }
+
+    /// Sets the hosts reached without the proxy.
+    pub fn with_no_proxy(mut self, no_proxy: Vec<String>) -> Self {
+        self.no_proxy = no_proxy;
+        self
+    }
+
+    /// Sets whether the proxy environment variables are honoured.
+    pub fn with_use_env(mut self, use_env: bool) -> Self {
+        self.use_env = use_env;
+        self
+    }
+
+    /// Reads the proxy settings from the `proxy` object of a service in the configuration file.
+    ///
+    /// ```json
+    /// {
+    ///     "url": "http://proxy.hpc.example.org:3128",
+    ///     "username": "me",
+    ///     "password": "secret",
+    ///     "no_proxy": ["localhost", ".internal.example.org"],
+    ///     "use_env": true
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Configuration` if a value has the wrong type or the URL is invalid.
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let mut proxy = ProxyConfig::default();
+        if let Some(url) = json.get("url") {
+            let url = url
+                .as_str()
+                .ok_or_else(|| Error::Configuration("'proxy.url' must be a string".to_string()))?;
+            proxy.url = Some(
+                Url::parse(url).map_err(|e| Error::Configuration(format!("Invalid 'proxy.url': {}", e)))?,
+            );
+        }
+        if let Some(username) = json["username"].as_str() {
+            proxy = proxy.with_credentials(username.to_string(), json["password"].as_str().map(str::to_string));
+        }
+        match &json["no_proxy"] {
+            Value::Null => {}
+            Value::Array(hosts) => {
+                for host in hosts {
+                    let host = host
+                        .as_str()
+                        .ok_or_else(|| Error::Configuration("'proxy.no_proxy' must be a list of hosts".to_string()))?;
+                    proxy.no_proxy.push(host.to_string());
+                }
+            }
+            _ => return Err(Error::Configuration("'proxy.no_proxy' must be a list of hosts".to_string())),
+        }
+        if let Some(use_env) = json.get("use_env") {
+            proxy.use_env = use_env
+                .as_bool()
+                .ok_or_else(|| Error::Configuration("'proxy.use_env' must be a boolean".to_string()))?;
+        }
+        Ok(proxy)
+    }
+
+    /// Applies these settings to a `reqwest` client builder.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Configuration` if the proxy URL is not supported.
+    pub(crate) fn apply(&self, builder: ClientBuilder) -> Result<ClientBuilder> {
+        let Some(ref url) = self.url else {
+            return Ok(if self.use_env { builder } else { builder.no_proxy() });
+        };
+        let mut proxy_url = url.clone();
+        if let Some(ref username) = self.username {
+            let invalid = |_| Error::Configuration(format!("Proxy URL {} cannot carry credentials", url));
+            proxy_url.set_username(username).map_err(invalid)?;
+            proxy_url.set_password(self.password.as_deref()).map_err(invalid)?;
+        }
+        let no_proxy = if !self.no_proxy.is_empty() {
+            self.no_proxy.clone()
+        } else if self.use_env {
+            no_proxy_from_env()
+        } else {
+            Vec::new()
+        };
+        // `Proxy::all(..).no_proxy(..)` would still send the proxy credentials to the hosts
+        // reached directly, so the matching is done here instead.
+        let proxy = Proxy::custom(move |target| {
+            let host = target.host_str()?;
+            if no_proxy.iter().any(|rule| matches_no_proxy(rule, host)) {
+                None
+            } else {
+                Some(proxy_url.clone())
+            }
+        });
+        Ok(builder.proxy(proxy))
+    }
+}
+
+/// Reads the hosts reached without a proxy from the `NO_PROXY` environment variable.
+fn no_proxy_from_env() -> Vec<String> {
+    std::env::var("NO_PROXY")
+        .or_else(|_| std::env::var("no_proxy"))
+        .map(|list| list.split(',').map(|host| host.trim().to_string()).filter(|h| !h.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Returns whether `host` is covered by a no-proxy rule: `*`, a host or domain name
+/// (matching its subdomains too, with or without a leading `.`), an IP address, or a CIDR range.
+fn matches_no_proxy(rule: &str, host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if rule == "*" {
+        return true;
+    }
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return match rule.split_once('/') {
+            Some((network, prefix)) => match (network.parse::<IpAddr>(), prefix.parse::<u32>()) {
+                (Ok(network), Ok(prefix)) => in_network(ip, network, prefix),
+                _ => false,
+            },
+            None => rule.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() == Ok(ip),
+        };
+    }
+    let domain = rule.trim_start_matches('.');
+    host.eq_ignore_ascii_case(domain)
+        || (host.len() > domain.len()
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+            && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain))
+}
+
+fn in_network(ip: IpAddr, network: IpAddr, prefix: u32) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) if prefix <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) if prefix <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::configuration::Configuration;
+    use crate::utils::transport::Transport;
+    use mockito::{mock, Matcher};
+
+    #[tokio::test]
+    async fn test_request_through_proxy() {
+        let proxy_url = Url::parse(&mockito::server_url()).unwrap();
+        // a proxy receives the absolute URL of the target in the request line
+        let _m = mock("GET", "http://tes.example.org/test")
+            .match_header("proxy-authorization", "Basic bWU6c2VjcmV0")
+            .with_status(200)
+            .with_body("via proxy")
+            .create();
+
+        let proxy = ProxyConfig::new(proxy_url).with_credentials("me".to_string(), Some("secret".to_string()));
+        let config = Configuration::new(Url::parse("http://tes.example.org").unwrap()).with_proxy(proxy);
+        let response = Transport::new(&config).unwrap().get("/test", None).await.unwrap();
+
+        assert_eq!(response, "via proxy");
+    }
+
+    #[tokio::test]
+    async fn test_no_proxy_bypasses_proxy() {
+        let _m = mock("GET", "/test")
+            .match_header("proxy-authorization", Matcher::Missing)
+            .with_status(200)
+            .with_body("direct")
+            .create();
+
+        // nothing listens on the proxy, so the request only succeeds if it bypasses it
+        let proxy = ProxyConfig::new(Url::parse("http://127.0.0.1:9").unwrap())
+            .with_credentials("me".to_string(), None)
+            .with_no_proxy(vec!["127.0.0.1".to_string()]);
+        let config = Configuration::new(Url::parse(&mockito::server_url()).unwrap()).with_proxy(proxy);
+        let response = Transport::new(&config).unwrap().get("/test", None).await.unwrap();
+
+        assert_eq!(response, "direct");
+    }
+
+    #[test]
+    fn test_no_proxy_rules() {
+        assert!(matches_no_proxy("*", "tes.example.org"));
+        assert!(matches_no_proxy("example.org", "tes.example.org"));
+        assert!(matches_no_proxy(".example.org", "example.org"));
+        assert!(!matches_no_proxy("example.org", "notexample.org"));
+        assert!(matches_no_proxy("10.0.0.0/8", "10.1.2.3"));
+        assert!(!matches_no_proxy("10.0.0.0/8", "11.1.2.3"));
+        assert!(matches_no_proxy("::1", "[::1]"));
+    }
+
+    #[test]
+    fn test_from_json() {
+        let proxy = ProxyConfig::from_json(&serde_json::json!({
+            "url": "http://proxy:3128",
+            "username": "me",
+            "no_proxy": ["localhost", ".internal.example.org"],
+            "use_env": false
+        }))
+        .unwrap();
+
+        assert_eq!(proxy.url, Some(Url::parse("http://proxy:3128").unwrap()));
+        assert_eq!(proxy.username.as_deref(), Some("me"));
+        assert_eq!(proxy.password, None);
+        assert_eq!(proxy.no_proxy, vec!["localhost", ".internal.example.org"]);
+        assert!(!proxy.use_env);
+
+        let invalid = ProxyConfig::from_json(&serde_json::json!({ "url": "not a url" }));
+        assert!(matches!(invalid, Err(Error::Configuration(_))));
+    }
+}
@@ -0,0 +1,323 @@
+use crate::utils::backend::{HttpRequest, HttpResponse, Method};
+use crate::utils::middleware::{Middleware, Next};
+use crate::utils::registry::Registry;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use tracing::debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use url::Url;
+
+/// A token-bucket budget: `per_second` requests on average, with bursts of up to `burst`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RateLimit {
+    /// The sustained number of requests per second.
+    pub per_second: f64,
+    /// The number of requests that can be sent at once after a quiet period.
+    #[serde(default = "default_burst")]
+    pub burst: u32,
+}
+
+fn default_burst() -> u32 {
+    1
+}
+
+impl RateLimit {
+    /// Creates a budget of `per_second` requests per second, with bursts of up to `burst`.
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        RateLimit { per_second, burst }
+    }
+}
+
+/// Client-side rate limits for the requests to a service.
+///
+/// Read requests (`GET`, `HEAD`, `OPTIONS`) and write requests (all others) have separate
+/// budgets; `None` leaves them unlimited. Requests over the limit wait for their turn instead
+/// of failing. All transports to the same base URL share the budgets, including clones of a
+/// transport, so they must use the same limits.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::configuration::Configuration;
+/// use ga4gh_sdk::utils::rate_limit::{RateLimit, RateLimitConfig};
+///
+/// let rate_limit = RateLimitConfig::default()
+///     .with_read(RateLimit::new(5.0, 10))
+///     .with_write(RateLimit::new(1.0, 2));
+/// let config = Configuration::new(url::Url::parse("https://tes.example.org").unwrap())
+///     .with_rate_limit(rate_limit);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// The budget for read requests.
+    pub read: Option<RateLimit>,
+    /// The budget for write requests.
+    pub write: Option<RateLimit>,
+}
+
+impl RateLimitConfig {
+    /// Sets the budget for read requests.
+    pub fn with_read(mut self, read: RateLimit) -> Self {
+        self.read = Some(read);
+        self
+    }
+
+    /// Sets the budget for write requests.
+    pub fn with_write(mut self, write: RateLimit) -> Self {
+        self.write = Some(write);
+        self
+    }
+
+    /// Returns whether no budget is set.
+    pub fn is_unlimited(&self) -> bool {
+        self.read.is_none() && self.write.is_none()
+    }
+
+    /// Checks that every budget allows some requests.
+    ///
+    /// # Returns
+    ///
+    /// `Error::Configuration` if a budget has a `per_second` that is not a positive number.
+    pub fn validate(&self) -> Result<()> {
+        for (kind, limit) in [("read", &self.read), ("write", &self.write)] {
+            if let Some(limit) = limit {
+                if !(limit.per_second > 0.0 && limit.per_second.is_finite()) {
+                    return Err(Error::Configuration(format!(
+                        "The {} rate limit must allow a positive number of requests per second, got {}",
+                        kind, limit.per_second
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A token bucket, allowing one request per token.
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    // may become negative: requests waiting for a token reserve it in advance
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        Bucket {
+            tokens: limit.burst as f64,
+            limit,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Takes a token and returns how long to wait before using it.
+    fn reserve(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst.max(1) as f64);
+        self.refilled_at = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.limit.per_second)
+        }
+    }
+}
+
+/// Delays requests so they stay within the budgets of a `RateLimitConfig`.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    read: Option<Mutex<Bucket>>,
+    write: Option<Mutex<Bucket>>,
+}
+
+/// The limiters in use, one per base URL.
+static SHARED_LIMITERS: Registry<Url, RateLimiter> = Registry::new();
+
+impl RateLimiter {
+    /// Creates a limiter with its own budgets.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The budgets for read and write requests.
+    ///
+    /// # Returns
+    ///
+    /// The limiter, or `Error::Configuration` if a budget is invalid.
+    pub fn new(config: &RateLimitConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(RateLimiter {
+            config: config.clone(),
+            read: config.read.clone().map(|limit| Mutex::new(Bucket::new(limit))),
+            write: config.write.clone().map(|limit| Mutex::new(Bucket::new(limit))),
+        })
+    }
+
+    /// Returns the limiter shared by all transports to `base_url`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - The base URL of the service.
+    /// * `config` - The budgets for read and write requests.
+    ///
+    /// # Returns
+    ///
+    /// The shared limiter, or `Error::Configuration` if a budget is invalid or if transports
+    /// to `base_url` with different budgets are still in use.
+    pub fn shared(base_url: &Url, config: &RateLimitConfig) -> Result<Arc<Self>> {
+        let limiter = SHARED_LIMITERS.get_or_try_insert(base_url.clone(), || RateLimiter::new(config))?;
+        if limiter.config != *config {
+            return Err(Error::Configuration(format!(
+                "Transports to {} are already in use with different rate limits",
+                base_url
+            )));
+        }
+        Ok(limiter)
+    }
+
+    /// Waits until a request with the given method is within its budget.
+    pub async fn acquire(&self, method: &Method) {
+        let is_read = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+        let bucket = if is_read { &self.read } else { &self.write };
+        let Some(bucket) = bucket else { return };
+        let delay = bucket.lock().unwrap_or_else(|e| e.into_inner()).reserve();
+        if !delay.is_zero() {
+            debug!("Rate limit reached, delaying {} request by {:?}", method, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Delays requests according to a `RateLimiter`.
+///
+/// Part of the default chain when the configuration sets rate limits, after the retry
+/// middleware so every attempt counts against the budget.
+#[derive(Debug, Clone)]
+pub struct RateLimitMiddleware {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitMiddleware {
+    /// Creates a middleware delaying requests with the given limiter.
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        RateLimitMiddleware { limiter }
+    }
+}
+
+#[async_trait]
+impl Middleware for RateLimitMiddleware {
+    async fn handle(&self, request: HttpRequest, next: Next<'_>) -> Result<HttpResponse> {
+        self.limiter.acquire(&request.method).await;
+        next.run(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::backend::{HttpBackend, StatusCode};
+    use crate::utils::configuration::Configuration;
+    use crate::utils::transport::Transport;
+
+    /// A backend answering every request immediately.
+    struct OkBackend;
+
+    #[async_trait]
+    impl HttpBackend for OkBackend {
+        async fn execute(&self, _request: HttpRequest) -> Result<HttpResponse> {
+            Ok(HttpResponse::new(StatusCode::OK, "ok"))
+        }
+    }
+
+    fn transport(base_url: &str, rate_limit: RateLimitConfig) -> Transport {
+        let config = Configuration::new(Url::parse(base_url).unwrap()).with_rate_limit(rate_limit);
+        Transport::with_backend(&config, Arc::new(OkBackend)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests_wait_for_budget() {
+        let transport = transport(
+            "https://limited.example.org",
+            RateLimitConfig::default().with_read(RateLimit::new(20.0, 2)),
+        );
+
+        let started = std::time::Instant::now();
+        for _ in 0..4 {
+            transport.get("/tasks", None).await.unwrap();
+        }
+        // two requests fit in the burst, the other two wait 50 ms each
+        assert!(started.elapsed() >= Duration::from_millis(90));
+
+        // writes have no budget and are not delayed
+        let started = std::time::Instant::now();
+        for _ in 0..4 {
+            transport.post("/tasks", None).await.unwrap();
+        }
+        assert!(started.elapsed() < Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn test_budget_shared_between_clones() {
+        let rate_limit = RateLimitConfig::default().with_write(RateLimit::new(10.0, 1));
+        let first = transport("https://shared.example.org", rate_limit.clone());
+        let second = first.clone();
+        let other = transport("https://shared.example.org", rate_limit);
+
+        let started = std::time::Instant::now();
+        let (a, b, c) = tokio::join!(
+            first.post("/tasks", None),
+            second.post("/tasks", None),
+            other.post("/tasks", None)
+        );
+        assert!(a.is_ok() && b.is_ok() && c.is_ok());
+        assert!(started.elapsed() >= Duration::from_millis(190));
+    }
+
+    #[test]
+    fn test_conflicting_limits_rejected() {
+        let slow = RateLimitConfig::default().with_write(RateLimit::new(1.0, 1));
+        let fast = RateLimitConfig::default().with_write(RateLimit::new(100.0, 1));
+        let base_url = Url::parse("https://conflict.example.org").unwrap();
+        let limiter = RateLimiter::shared(&base_url, &slow).unwrap();
+
+        assert!(matches!(RateLimiter::shared(&base_url, &fast), Err(Error::Configuration(_))));
+        let config = Configuration::new(base_url.clone()).with_rate_limit(fast.clone());
+        assert!(matches!(Transport::new(&config), Err(Error::Configuration(_))));
+        let transport = Transport::with_backend(&config, Arc::new(OkBackend));
+        assert!(matches!(transport, Err(Error::Configuration(_))));
+
+        // the limits can change once the transports using the old ones are dropped
+        drop(limiter);
+        assert!(RateLimiter::shared(&base_url, &fast).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_limits_rejected() {
+        for per_second in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let config = RateLimitConfig::default().with_read(RateLimit::new(per_second, 1));
+            assert!(matches!(config.validate(), Err(Error::Configuration(_))));
+            assert!(matches!(RateLimiter::new(&config), Err(Error::Configuration(_))));
+        }
+        let config = Configuration::new(Url::parse("https://invalid.example.org").unwrap())
+            .with_rate_limit(RateLimitConfig::default().with_write(RateLimit::new(0.0, 1)));
+        assert!(matches!(Transport::new(&config), Err(Error::Configuration(_))));
+    }
+
+    #[test]
+    fn test_deserialize_rate_limit() {
+        let config: RateLimitConfig = serde_json::from_value(serde_json::json!({
+            "read": {"per_second": 5, "burst": 10},
+            "write": {"per_second": 0.5}
+        }))
+        .unwrap();
+
+        assert_eq!(config.read, Some(RateLimit::new(5.0, 10)));
+        assert_eq!(config.write, Some(RateLimit::new(0.5, 1)));
+    }
+}
@@ -0,0 +1,90 @@
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+/// Values shared by the transports that ask for them with the same key, such as the connection
+/// pool of a set of client settings or the circuit breaker of a service.
+///
+/// The registry only keeps weak references: a value is dropped with the last transport using
+/// it, and its entry is pruned on the next lookup.
+pub(crate) struct Registry<K, V> {
+    entries: Mutex<Vec<(K, Weak<V>)>>,
+}
+
+impl<K: PartialEq, V> Registry<K, V> {
+    /// Creates an empty registry, usable as a `static`.
+    pub(crate) const fn new() -> Self {
+        Registry {
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Locks the entries, pruning the ones whose value was dropped.
+    fn entries(&self) -> MutexGuard<'_, Vec<(K, Weak<V>)>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|(_, value)| value.strong_count() > 0);
+        entries
+    }
+
+    /// Returns the value registered for `key`, or registers the one built by `create`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key the value is shared under.
+    /// * `create` - Builds the value if none is registered for `key`.
+    ///
+    /// # Returns
+    ///
+    /// The shared value, or the error of `create`.
+    pub(crate) fn get_or_try_insert<E>(&self, key: K, create: impl FnOnce() -> Result<V, E>) -> Result<Arc<V>, E> {
+        let mut entries = self.entries();
+        let registered = entries
+            .iter()
+            .filter(|(registered, _)| *registered == key)
+            .find_map(|(_, value)| value.upgrade());
+        if let Some(value) = registered {
+            return Ok(value);
+        }
+        let value = Arc::new(create()?);
+        entries.push((key, Arc::downgrade(&value)));
+        Ok(value)
+    }
+
+    /// Returns the value registered for `key`, or registers the one built by `create`.
+    pub(crate) fn get_or_insert(&self, key: K, create: impl FnOnce() -> V) -> Arc<V> {
+        match self.get_or_try_insert(key, || Ok::<V, Infallible>(create())) {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Returns the values still in use.
+    pub(crate) fn values(&self) -> Vec<Arc<V>> {
+        self.entries().iter().filter_map(|(_, value)| value.upgrade()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_shared_while_in_use() {
+        let registry: Registry<&str, String> = Registry::new();
+        let create = |value: &str| Ok::<_, ()>(value.to_string());
+
+        let first = registry.get_or_try_insert("a", || create("first")).unwrap();
+        let second = registry.get_or_try_insert("a", || create("second")).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        let other = registry.get_or_insert("b", || "other".to_string());
+        assert_eq!(registry.values().len(), 2);
+        assert_eq!(registry.get_or_try_insert("c", || Err("invalid")), Err("invalid"));
+
+        // dropped with the last user, and pruned
+        drop(other);
+        assert_eq!(registry.values(), vec![first.clone()]);
+        drop((first, second));
+        assert!(registry.values().is_empty());
+        assert_eq!(*registry.get_or_try_insert("a", || create("third")).unwrap(), "third");
+        assert!(registry.entries().is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+use crate::utils::backend::HttpResponse;
+use crate::utils::timeouts::deserialize_millis;
+use crate::{Error, Result};
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::Method;
+use std::time::{Duration, SystemTime};
+
+/// A policy describing when and how failed requests are retried.
+///
+/// A request is retried when it fails with a network error, a timeout, or one of the
+/// `retry_status_codes`, until `max_attempts` attempts have been made. The delay between
+/// attempts grows exponentially from `base_backoff` up to `max_backoff`. When the server
+/// sends a `Retry-After` header, its value is used instead; if it asks to wait longer than
+/// `max_backoff`, the request is not retried and the response is returned, with the header.
+///
+/// Non-idempotent requests (`POST`) are only retried when `retry_non_idempotent` is set,
+/// except for `429 Too Many Requests`, which guarantees the request was not processed.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::configuration::Configuration;
+/// use ga4gh_sdk::utils::retry::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::default()
+///     .with_max_attempts(5)
+///     .with_backoff(Duration::from_millis(500), Duration::from_secs(30))
+///     .with_retry_non_idempotent(true);
+/// let config = Configuration::new(url::Url::parse("https://api.example.com").unwrap())
+///     .with_retry_policy(policy);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one. `1` disables retries.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    #[serde(rename = "base_backoff_ms", deserialize_with = "deserialize_millis")]
+    pub base_backoff: Duration,
+    /// The upper bound of the delay between two attempts.
+    #[serde(rename = "max_backoff_ms", deserialize_with = "deserialize_millis")]
+    pub max_backoff: Duration,
+    /// Whether to randomize each delay to between half and all of its value.
+    pub jitter: bool,
+    /// The HTTP status codes that trigger a retry.
+    #[serde(rename = "status_codes")]
+    pub retry_status_codes: Vec<u16>,
+    /// Whether non-idempotent requests (`POST`) are retried as well.
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            retry_status_codes: vec![429, 502, 503, 504],
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy that never retries.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Sets the maximum number of attempts, including the first one.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the delay before the first retry and the upper bound of the delay.
+    pub fn with_backoff(mut self, base_backoff: Duration, max_backoff: Duration) -> Self {
+        self.base_backoff = base_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Enables or disables randomization of the delays.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the HTTP status codes that trigger a retry.
+    pub fn with_retry_status_codes(mut self, retry_status_codes: Vec<u16>) -> Self {
+        self.retry_status_codes = retry_status_codes;
+        self
+    }
+
+    /// Sets whether non-idempotent requests (`POST`) are retried as well.
+    pub fn with_retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    /// Decides whether a failed attempt should be retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method of the request.
+    /// * `attempt` - The number of the attempt that failed, starting at 1.
+    /// * `outcome` - The response or error returned by the attempt.
+    ///
+    /// # Returns
+    ///
+    /// The delay to wait before the next attempt, or `None` if the request should not be retried.
+    pub fn retry_delay(&self, method: &Method, attempt: u32, outcome: &Result<HttpResponse>) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let idempotent = is_idempotent(method) || self.retry_non_idempotent;
+        let (status, headers) = match outcome {
+            Ok(response) => (response.status, &response.headers),
+            Err(Error::Http { status, headers, .. }) => (*status, &**headers),
+            Err(Error::Transport(_) | Error::Timeout) if idempotent => return Some(self.backoff(attempt)),
+            Err(_) => return None,
+        };
+        if !self.retry_status_codes.contains(&status.as_u16()) {
+            return None;
+        }
+        if !idempotent && status.as_u16() != 429 {
+            return None;
+        }
+        match retry_after(headers) {
+            // retrying before the server is ready would only fail again
+            Some(delay) if delay > self.max_backoff => None,
+            Some(delay) => Some(delay),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+
+    /// Computes the exponential backoff delay after the given attempt.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_backoff.saturating_mul(factor).min(self.max_backoff);
+        if self.jitter && !delay.is_zero() {
+            rand::thread_rng().gen_range(delay / 2..=delay)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Returns whether a request with the given method can safely be sent more than once.
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE
+    )
+}
+
+/// Parses a `Retry-After` header given either in seconds or as an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    fn http_response(status: u16, headers: HeaderMap) -> Result<HttpResponse> {
+        let mut response = HttpResponse::new(StatusCode::from_u16(status).unwrap(), "");
+        response.headers = headers;
+        Ok(response)
+    }
+
+    #[test]
+    fn test_backoff_grows_exponentially() {
+        let policy = RetryPolicy::default()
+            .with_max_attempts(10)
+            .with_jitter(false)
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(500));
+        let error = http_response(503, HeaderMap::new());
+
+        assert_eq!(policy.retry_delay(&Method::GET, 1, &error), Some(Duration::from_millis(100)));
+        assert_eq!(policy.retry_delay(&Method::GET, 2, &error), Some(Duration::from_millis(200)));
+        assert_eq!(policy.retry_delay(&Method::GET, 3, &error), Some(Duration::from_millis(400)));
+        assert_eq!(policy.retry_delay(&Method::GET, 4, &error), Some(Duration::from_millis(500)));
+        assert_eq!(policy.retry_delay(&Method::GET, 10, &error), None);
+    }
+
+    #[test]
+    fn test_retry_decisions() {
+        let policy = RetryPolicy::default();
+
+        assert!(policy.retry_delay(&Method::GET, 1, &http_response(500, HeaderMap::new())).is_none());
+        assert!(policy.retry_delay(&Method::POST, 1, &http_response(503, HeaderMap::new())).is_none());
+        assert!(policy.retry_delay(&Method::POST, 1, &http_response(429, HeaderMap::new())).is_some());
+        assert!(policy.retry_delay(&Method::POST, 1, &Err(Error::Timeout)).is_none());
+        assert!(policy.retry_delay(&Method::GET, 1, &Err(Error::Timeout)).is_some());
+
+        let policy = policy.with_retry_non_idempotent(true);
+        assert!(policy.retry_delay(&Method::POST, 1, &http_response(503, HeaderMap::new())).is_some());
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let policy = RetryPolicy::default().with_backoff(Duration::from_millis(100), Duration::from_secs(5));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "2".parse().unwrap());
+        let error = http_response(429, headers);
+        assert_eq!(policy.retry_delay(&Method::GET, 1, &error), Some(Duration::from_secs(2)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "120".parse().unwrap());
+        let error = http_response(503, headers);
+        assert_eq!(policy.retry_delay(&Method::GET, 1, &error), None);
+    }
+
+    #[test]
+    fn test_deserialize_policy() {
+        let policy: RetryPolicy = serde_json::from_value(serde_json::json!({
+            "max_attempts": 5,
+            "base_backoff_ms": 50,
+            "status_codes": [503]
+        }))
+        .unwrap();
+
+        assert_eq!(policy.max_attempts, 5);
+        assert_eq!(policy.base_backoff, Duration::from_millis(50));
+        assert_eq!(policy.max_backoff, RetryPolicy::default().max_backoff);
+        assert_eq!(policy.retry_status_codes, vec![503]);
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+use url::Url;
+
+/// How `TES::create` stages the local inputs of a task before sending it.
+///
+/// An input is local when its URL is a `file://` URL or a bare path, relative to the current
+/// directory. Files up to `inline_max_bytes` holding UTF-8 text are inlined in the task as
+/// `content`. Other files and directories are uploaded to the `target` under
+/// `<prefix>/<sha256>/<name>`, so staging the same content again uploads it to the same place,
+/// and the input URL is rewritten to the uploaded copy.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::staging::{StagingConfig, StagingTarget};
+/// use ga4gh_sdk::utils::configuration::Configuration;
+///
+/// let target = StagingTarget::S3 {
+///     endpoint: url::Url::parse("http://localhost:9000").unwrap(),
+///     bucket: "tes-inputs".to_string(),
+///     region: "us-east-1".to_string(),
+///     access_key_id: None,
+///     secret_access_key: None,
+///     session_token: None,
+/// };
+/// let staging = StagingConfig::new(target).with_inline_max_bytes(64 * 1024);
+/// let config = Configuration::new(url::Url::parse("http://localhost:8000").unwrap())
+///     .with_staging(staging);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StagingConfig {
+    /// Where files that are not inlined are uploaded; without one, such files are an error.
+    #[serde(default)]
+    pub target: Option<StagingTarget>,
+    /// The prefix of the keys files are uploaded under.
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    /// The size up to which text files are inlined, in bytes; `0` disables inlining.
+    #[serde(default)]
+    pub inline_max_bytes: u64,
+}
+
+fn default_prefix() -> String {
+    "ga4gh-staging".to_string()
+}
+
+impl Default for StagingConfig {
+    fn default() -> Self {
+        StagingConfig {
+            target: None,
+            prefix: default_prefix(),
+            inline_max_bytes: 0,
+        }
+    }
+}
+
+impl StagingConfig {
+    /// Creates a configuration uploading local inputs to the given target.
+    pub fn new(target: StagingTarget) -> Self {
+        StagingConfig {
+            target: Some(target),
+            ..StagingConfig::default()
+        }
+    }
+
+    /// Sets the prefix of the keys files are uploaded under.
+    pub fn with_prefix(mut self, prefix: String) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Sets the size up to which text files are inlined, in bytes.
+    pub fn with_inline_max_bytes(mut self, inline_max_bytes: u64) -> Self {
+        self.inline_max_bytes = inline_max_bytes;
+        self
+    }
+}
+
+/// A location local inputs are uploaded to, reachable by the TES server.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StagingTarget {
+    /// Uploads each file with a `PUT` request to `url` followed by its key. The task reads it
+    /// from the same URL.
+    Http {
+        url: Url,
+        /// The token sent as `Authorization: Bearer <token>` with the uploads.
+        #[serde(default)]
+        bearer_token: Option<String>,
+    },
+    /// Uploads each file to a bucket of an S3-compatible object store, addressed by path, e.g.
+    /// `<endpoint>/<bucket>/<key>`, signing the requests with AWS Signature Version 4. The
+    /// task reads it from `s3://<bucket>/<key>`. Credentials that are not set are read from
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`.
+    S3 {
+        endpoint: Url,
+        bucket: String,
+        #[serde(default = "default_region")]
+        region: String,
+        #[serde(default)]
+        access_key_id: Option<String>,
+        #[serde(default)]
+        secret_access_key: Option<String>,
+        #[serde(default)]
+        session_token: Option<String>,
+    },
+    /// Copies each file into a directory shared with the TES server. The task reads it from
+    /// `url` followed by its key, or from the `file://` URL of the copy if `url` is not set,
+    /// e.g. when the directory is mounted at another path on the server.
+    SharedFs {
+        path: PathBuf,
+        #[serde(default)]
+        url: Option<Url>,
+    },
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
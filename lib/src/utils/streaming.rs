@@ -0,0 +1,194 @@
+use crate::utils::backend::{HeaderMap, StatusCode};
+use crate::utils::body::{Body, ByteStream};
+use crate::Result;
+use bytes::Bytes;
+use futures::TryStreamExt;
+use reqwest::header::{CONTENT_LENGTH, CONTENT_RANGE};
+use std::fmt;
+use tokio::io::AsyncRead;
+use tokio_util::io::StreamReader;
+
+/// A range of bytes to request from a resource, sent as a `Range` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// From the given offset to the end of the resource.
+    From(u64),
+    /// From the first to the last offset, both included.
+    Inclusive(u64, u64),
+    /// The given number of bytes at the end of the resource.
+    Last(u64),
+}
+
+impl fmt::Display for ByteRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ByteRange::From(start) => write!(f, "bytes={}-", start),
+            ByteRange::Inclusive(start, end) => write!(f, "bytes={}-{}", start, end),
+            ByteRange::Last(length) => write!(f, "bytes=-{}", length),
+        }
+    }
+}
+
+/// A successful response whose body is read as a stream.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ga4gh_sdk::utils::configuration::Configuration;
+/// use ga4gh_sdk::utils::streaming::ByteRange;
+/// use ga4gh_sdk::utils::transport::Transport;
+///
+/// # async fn example() -> ga4gh_sdk::Result<()> {
+/// let config = Configuration::new(url::Url::parse("https://drs.example.org").unwrap());
+/// let transport = Transport::new(&config)?;
+///
+/// // resume a download at byte 1024
+/// let response = transport.get_stream("/objects/123/content", None, Some(ByteRange::From(1024))).await?;
+/// println!("{:?} of {:?} bytes", response.content_length(), response.total_length());
+/// let mut reader = response.into_async_read();
+/// let mut file = tokio::fs::File::create("object.bin").await?;
+/// tokio::io::copy(&mut reader, &mut file).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StreamResponse {
+    /// The HTTP status code of the response, `206 Partial Content` for a served range.
+    pub status: StatusCode,
+    /// The response headers.
+    pub headers: HeaderMap,
+    body: Body,
+}
+
+impl StreamResponse {
+    pub(crate) fn new(status: StatusCode, headers: HeaderMap, body: Body) -> Self {
+        StreamResponse { status, headers, body }
+    }
+
+    /// Returns the length of this response's body, from the `Content-Length` header.
+    pub fn content_length(&self) -> Option<u64> {
+        header_u64(&self.headers, CONTENT_LENGTH.as_str()).or_else(|| self.body.length())
+    }
+
+    /// Returns the length of the whole resource: the total of the `Content-Range` header for a
+    /// partial response, or the content length otherwise.
+    pub fn total_length(&self) -> Option<u64> {
+        if !self.is_partial() {
+            return self.content_length();
+        }
+        let content_range = self.headers.get(CONTENT_RANGE)?.to_str().ok()?;
+        content_range.rsplit_once('/')?.1.trim().parse().ok()
+    }
+
+    /// Returns whether the server sent only the requested range (`206 Partial Content`).
+    ///
+    /// Servers that do not support ranges send the whole resource with `200 OK` instead.
+    pub fn is_partial(&self) -> bool {
+        self.status == StatusCode::PARTIAL_CONTENT
+    }
+
+    /// Turns the response into a stream of body chunks.
+    pub fn into_stream(self) -> ByteStream {
+        self.body.into_stream()
+    }
+
+    /// Turns the response into an `AsyncRead` of the body.
+    ///
+    /// Errors while streaming are reported as `std::io::Error`s wrapping the SDK's `Error`.
+    pub fn into_async_read(self) -> impl AsyncRead + Send + Unpin {
+        StreamReader::new(self.into_stream().map_err(std::io::Error::other))
+    }
+
+    /// Reads the whole body into memory.
+    pub async fn bytes(self) -> Result<Bytes> {
+        self.body.collect().await
+    }
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::configuration::Configuration;
+    use crate::utils::transport::Transport;
+    use futures::StreamExt;
+    use mockito::{mock, Matcher};
+    use tokio::io::AsyncReadExt;
+    use url::Url;
+
+    fn transport() -> Transport {
+        Transport::new(&Configuration::new(Url::parse(&mockito::server_url()).unwrap())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_stream() {
+        let content = vec![7u8; 100_000];
+        let _m = mock("GET", "/objects/1")
+            .with_status(200)
+            .with_header("content-type", "application/octet-stream")
+            .with_body(&content)
+            .create();
+
+        let response = transport().get_stream("/objects/1", None, None).await.unwrap();
+        assert_eq!(response.content_length(), Some(100_000));
+        assert_eq!(response.total_length(), Some(100_000));
+        assert!(!response.is_partial());
+
+        let mut stream = response.into_stream();
+        let mut received = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            received.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(received, content);
+    }
+
+    #[tokio::test]
+    async fn test_get_stream_range() {
+        let _m = mock("GET", "/objects/1")
+            .match_header("range", "bytes=4-")
+            .with_status(206)
+            .with_header("content-range", "bytes 4-9/10")
+            .with_body("456789")
+            .create();
+
+        let response = transport().get_stream("/objects/1", None, Some(ByteRange::From(4))).await.unwrap();
+        assert!(response.is_partial());
+        assert_eq!(response.content_length(), Some(6));
+        assert_eq!(response.total_length(), Some(10));
+
+        let mut content = String::new();
+        response.into_async_read().read_to_string(&mut content).await.unwrap();
+        assert_eq!(content, "456789");
+    }
+
+    #[tokio::test]
+    async fn test_get_stream_error_status() {
+        let _m = mock("GET", "/objects/2").with_status(404).with_body("no such object").create();
+
+        let response = transport().get_stream("/objects/2", None, None).await;
+        match response {
+            Err(crate::Error::Http { status, .. }) => assert_eq!(status, StatusCode::NOT_FOUND),
+            other => panic!("expected an HTTP error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_stream() {
+        let _m = mock("PUT", "/upload")
+            .match_header("content-type", "text/plain")
+            .match_header("content-length", "11")
+            .match_body(Matcher::Exact("hello world".to_string()))
+            .with_status(201)
+            .with_body("stored")
+            .create();
+
+        let chunks = futures::stream::iter(vec![Ok(Bytes::from("hello ")), Ok(Bytes::from("world"))]);
+        let body = Body::from_stream(chunks).with_length(11);
+        let response = transport().put_stream("/upload", body, "text/plain").await;
+
+        assert_eq!(response.unwrap(), "stored");
+    }
+}
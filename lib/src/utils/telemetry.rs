@@ -0,0 +1,227 @@
+use crate::utils::backend::{HttpRequest, HttpResponse};
+use crate::utils::middleware::{Middleware, Next};
+use crate::Result;
+use async_trait::async_trait;
+use reqwest::header::HeaderValue;
+use std::future::Future;
+use std::time::Instant;
+use tracing::field::{display, Empty};
+use tracing::{Instrument, Span};
+
+#[cfg(feature = "opentelemetry")]
+pub use {opentelemetry, tracing_opentelemetry};
+
+/// The name of the W3C Trace Context header.
+pub const TRACEPARENT: &str = "traceparent";
+
+/// Creates the span of one API operation, such as creating or cancelling a task.
+///
+/// The span declares the fields recorded while the operation runs: `task_id` and `run_id`
+/// once known, `http.method` and `http.status_code` of its request, `latency_ms` and, on
+/// failure, `error`.
+///
+/// # Arguments
+///
+/// * `service` - The kind of service, e.g. `tes`.
+/// * `operation` - The name of the operation, e.g. `create_task`.
+pub(crate) fn operation_span(service: &'static str, operation: &'static str) -> Span {
+    tracing::info_span!(
+        "ga4gh.operation",
+        otel.name = operation,
+        service,
+        operation,
+        task_id = Empty,
+        run_id = Empty,
+        http.method = Empty,
+        http.status_code = Empty,
+        latency_ms = Empty,
+        error = Empty,
+    )
+}
+
+/// Runs an API operation inside its span, recording its latency and error.
+pub(crate) async fn traced<T>(span: Span, operation: impl Future<Output = Result<T>>) -> Result<T> {
+    let started = Instant::now();
+    let result = operation.instrument(span.clone()).await;
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+    if let Err(ref e) = result {
+        span.record("error", display(e));
+        tracing::debug!(parent: &span, "Operation failed: {}", e);
+    }
+    result
+}
+
+/// Records a span per request attempt and propagates its trace context to the server.
+///
+/// The span, `http.request`, is a child of the operation span and records the HTTP method,
+/// URL, status and latency. A W3C `traceparent` header is added unless the request already
+/// has one. With the `opentelemetry` feature and a `tracing_opentelemetry` layer installed,
+/// the header carries the IDs of the exported span, so server-side spans join the client's
+/// trace; otherwise it carries fresh random IDs, which still let the server's logs be
+/// correlated with the client's.
+///
+/// Part of the default chain, after the retry middleware so every attempt is recorded.
+#[derive(Debug, Clone, Default)]
+pub struct TracingMiddleware;
+
+impl TracingMiddleware {
+    /// Creates a middleware tracing every request attempt.
+    pub fn new() -> Self {
+        TracingMiddleware
+    }
+}
+
+#[async_trait]
+impl Middleware for TracingMiddleware {
+    async fn handle(&self, mut request: HttpRequest, next: Next<'_>) -> Result<HttpResponse> {
+        let span = tracing::debug_span!(
+            "http.request",
+            otel.kind = "client",
+            http.method = %request.method,
+            url = %request.url,
+            http.status_code = Empty,
+            latency_ms = Empty,
+            error = Empty,
+        );
+        Span::current().record("http.method", display(&request.method));
+        if !request.headers.contains_key(TRACEPARENT) {
+            if let Ok(traceparent) = HeaderValue::from_str(&traceparent(&span)) {
+                request.headers.insert(TRACEPARENT, traceparent);
+            }
+        }
+
+        let started = Instant::now();
+        let result = next.run(request).instrument(span.clone()).await;
+        span.record("latency_ms", started.elapsed().as_millis() as u64);
+        match result {
+            Ok(ref response) => {
+                span.record("http.status_code", response.status.as_u16());
+                Span::current().record("http.status_code", response.status.as_u16());
+            }
+            Err(ref e) => {
+                span.record("error", display(e));
+            }
+        }
+        result
+    }
+}
+
+/// Returns the `traceparent` header value for the given span.
+#[cfg(feature = "opentelemetry")]
+fn traceparent(span: &Span) -> String {
+    use opentelemetry::trace::TraceContextExt;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let context = span.context();
+    let span_context = context.span().span_context().clone();
+    if span_context.is_valid() {
+        format!(
+            "00-{}-{}-{:02x}",
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags().to_u8()
+        )
+    } else {
+        random_traceparent()
+    }
+}
+
+/// Returns the `traceparent` header value for the given span.
+#[cfg(not(feature = "opentelemetry"))]
+fn traceparent(_span: &Span) -> String {
+    random_traceparent()
+}
+
+/// Returns a `traceparent` header value with random, sampled trace and parent IDs.
+fn random_traceparent() -> String {
+    // all-zero IDs are invalid
+    let trace_id = rand::random::<u128>().max(1);
+    let parent_id = rand::random::<u64>().max(1);
+    format!("00-{:032x}-{:016x}-01", trace_id, parent_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::backend::{HttpBackend, StatusCode};
+    use crate::utils::configuration::Configuration;
+    use crate::utils::transport::Transport;
+    use std::sync::Arc;
+    use url::Url;
+
+    /// A backend answering every request with its `traceparent` header.
+    struct TraceparentBackend;
+
+    #[async_trait]
+    impl HttpBackend for TraceparentBackend {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+            let traceparent = request
+                .headers
+                .get(TRACEPARENT)
+                .map(|v| v.to_str().unwrap().to_string())
+                .unwrap_or_default();
+            Ok(HttpResponse::new(StatusCode::OK, traceparent))
+        }
+    }
+
+    fn transport() -> Transport {
+        let config = Configuration::new(Url::parse("https://api.example.com").unwrap());
+        Transport::with_backend(&config, Arc::new(TraceparentBackend)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_traceparent_added() {
+        let transport = transport();
+        let first = transport.get("/tasks", None).await.unwrap();
+        let second = transport.get("/tasks", None).await.unwrap();
+
+        let parts: Vec<&str> = first.split('-').collect();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], "00");
+        assert_eq!(parts[1].len(), 32);
+        assert_eq!(parts[2].len(), 16);
+        assert!(parts[1..3].iter().all(|id| id.chars().all(|c| c.is_ascii_hexdigit())));
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_existing_traceparent_kept() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(TRACEPARENT, traceparent.parse().unwrap());
+        let transport = transport().with_middlewares(vec![
+            Arc::new(crate::utils::middleware::HeadersMiddleware::new(headers)),
+            Arc::new(TracingMiddleware::new()),
+        ]);
+
+        assert_eq!(transport.get("/tasks", None).await.unwrap(), traceparent);
+    }
+
+    #[tokio::test]
+    async fn test_traced_operation_returns_result() {
+        let span = operation_span("tes", "get_task");
+        span.record("task_id", "task-1");
+        let result: Result<u32> = traced(span, async { Ok(7) }).await;
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    #[tokio::test]
+    async fn test_traceparent_from_opentelemetry_span() {
+        use opentelemetry::trace::{TraceContextExt, TracerProvider};
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let span = operation_span("tes", "list_tasks");
+        let trace_id = span.context().span().span_context().trace_id();
+        let traceparent = traced(span, transport().get("/tasks", None)).await.unwrap();
+
+        assert!(traceparent.starts_with(&format!("00-{}-", trace_id)));
+        assert!(traceparent.ends_with("-01"));
+    }
+}
@@ -1,4 +1,7 @@
+use crate::utils::cassette::{CassetteConfig, CassetteMode};
+use crate::utils::configuration::Configuration;
 use std::env;
+use std::path::PathBuf;
 use std::process::Command;
 use std::str;
 use std::sync::Once;
@@ -30,3 +33,36 @@ pub async fn ensure_funnel_running() -> String {
     let funnel_url = format!("{}:{}", FUNNEL_HOST, FUNNEL_PORT);
     funnel_url
 }
+
+/// Returns the configuration for the integration test `name`, talking to Funnel.
+///
+/// With `GA4GH_CASSETTE=record`, the test's traffic is recorded to
+/// `lib/tests/cassettes/<name>.yaml`; with `GA4GH_CASSETTE=replay`, it is replayed from there
+/// and Funnel does not need to run. Tests without a recording replay the synthetic fixture in
+/// `lib/tests/cassettes/synthetic/<name>.yaml` instead, which was not recorded with Funnel.
+pub async fn funnel_configuration(name: &str) -> Configuration {
+    let mode = match env::var("GA4GH_CASSETTE").as_deref() {
+        Ok("record") => Some(CassetteMode::Record),
+        Ok("replay") => Some(CassetteMode::Replay),
+        Ok(other) => panic!("GA4GH_CASSETTE must be `record` or `replay`, not `{}`", other),
+        Err(_) => None,
+    };
+    let funnel_url = match mode {
+        Some(CassetteMode::Replay) => format!("{}:{}", FUNNEL_HOST, FUNNEL_PORT),
+        _ => ensure_funnel_running().await,
+    };
+    let funnel_url = url::Url::parse(&funnel_url).expect("Invalid URL");
+    let config = Configuration::new(funnel_url);
+    match mode {
+        Some(mode) => {
+            let cassettes = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("cassettes");
+            let file = format!("{}.yaml", name);
+            let mut path = cassettes.join(&file);
+            if mode == CassetteMode::Replay && !path.exists() {
+                path = cassettes.join("synthetic").join(&file);
+            }
+            config.with_cassette(CassetteConfig::new(path, mode))
+        }
+        None => config,
+    }
+}
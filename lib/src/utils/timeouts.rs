@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+/// Timeouts applied to every request of a `Transport`.
+///
+/// All timeouts are disabled by default, except that fetching the service information gives
+/// up after 10 seconds without a `request` timeout. When one expires, the request fails with
+/// `Error::Timeout`, which the `RetryPolicy` retries for idempotent requests.
+///
+/// # Examples
+///
+/// ```
+/// use ga4gh_sdk::utils::configuration::Configuration;
+/// use ga4gh_sdk::utils::timeouts::TimeoutConfig;
+/// use std::time::Duration;
+///
+/// let timeouts = TimeoutConfig::default()
+///     .with_connect(Duration::from_secs(5))
+///     .with_request(Duration::from_secs(60));
+/// let config = Configuration::new(url::Url::parse("https://api.example.com").unwrap())
+///     .with_timeouts(timeouts);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TimeoutConfig {
+    /// The maximum time to establish a connection, including the TLS handshake.
+    #[serde(rename = "connect_ms", deserialize_with = "deserialize_optional_millis")]
+    pub connect: Option<Duration>,
+    /// The maximum time to wait for the response headers after starting the request, or for
+    /// the next part of the body.
+    #[serde(rename = "read_ms", deserialize_with = "deserialize_optional_millis")]
+    pub read: Option<Duration>,
+    /// The maximum time for a whole request attempt, from connecting to reading the full body.
+    #[serde(rename = "request_ms", deserialize_with = "deserialize_optional_millis")]
+    pub request: Option<Duration>,
+}
+
+impl TimeoutConfig {
+    /// Sets the maximum time to establish a connection.
+    pub fn with_connect(mut self, connect: Duration) -> Self {
+        self.connect = Some(connect);
+        self
+    }
+
+    /// Sets the maximum time to wait for the response headers or for the next part of the body.
+    pub fn with_read(mut self, read: Duration) -> Self {
+        self.read = Some(read);
+        self
+    }
+
+    /// Sets the maximum time for a whole request attempt.
+    pub fn with_request(mut self, request: Duration) -> Self {
+        self.request = Some(request);
+        self
+    }
+}
+
+pub(crate) fn deserialize_millis<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let millis = <u64 as serde::Deserialize>::deserialize(deserializer)?;
+    Ok(Duration::from_millis(millis))
+}
+
+pub(crate) fn deserialize_optional_millis<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    let millis = <Option<u64> as serde::Deserialize>::deserialize(deserializer)?;
+    Ok(millis.map(Duration::from_millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::configuration::Configuration;
+    use crate::utils::retry::RetryPolicy;
+    use crate::utils::transport::Transport;
+    use crate::Error;
+    use mockito::mock;
+    use std::io::Write;
+    use url::Url;
+
+    fn config(timeouts: TimeoutConfig) -> Configuration {
+        Configuration::new(Url::parse(&mockito::server_url()).unwrap())
+            .with_retry_policy(RetryPolicy::none())
+            .with_timeouts(timeouts)
+    }
+
+    fn slow_body(w: &mut dyn Write) -> std::io::Result<()> {
+        w.write_all(b"slow")?;
+        std::thread::sleep(Duration::from_millis(500));
+        w.write_all(b" body")
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let _m = mock("GET", "/slow").with_status(200).with_body_from_fn(slow_body).create();
+
+        let timeouts = TimeoutConfig::default().with_request(Duration::from_millis(100));
+        let response = Transport::new(&config(timeouts)).unwrap().get("/slow", None).await;
+
+        assert!(matches!(response, Err(Error::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout() {
+        let _m = mock("GET", "/slow").with_status(200).with_body_from_fn(slow_body).create();
+
+        let timeouts = TimeoutConfig::default().with_read(Duration::from_millis(100));
+        let response = Transport::new(&config(timeouts)).unwrap().get("/slow", None).await;
+        assert!(matches!(response, Err(Error::Timeout)));
+
+        let timeouts = TimeoutConfig::default().with_read(Duration::from_secs(2));
+        let response = Transport::new(&config(timeouts)).unwrap().get("/slow", None).await;
+        assert_eq!(response.unwrap(), "slow body");
+    }
+
+    #[tokio::test]
+    async fn test_per_call_timeout_override() {
+        let _m = mock("GET", "/slow").with_status(200).with_body_from_fn(slow_body).create();
+
+        let timeouts = TimeoutConfig::default().with_request(Duration::from_secs(5));
+        let transport = Transport::new(&config(timeouts)).unwrap();
+        let response = transport.clone().with_timeout(Duration::from_millis(100)).get("/slow", None).await;
+        assert!(matches!(response, Err(Error::Timeout)));
+
+        assert_eq!(transport.get("/slow", None).await.unwrap(), "slow body");
+    }
+
+    #[test]
+    fn test_deserialize_timeouts() {
+        let timeouts: TimeoutConfig =
+            serde_json::from_value(serde_json::json!({ "connect_ms": 5000, "request_ms": null })).unwrap();
+
+        assert_eq!(timeouts.connect, Some(Duration::from_secs(5)));
+        assert_eq!(timeouts.read, None);
+        assert_eq!(timeouts.request, None);
+    }
+}
@@ -0,0 +1,421 @@
+use crate::{Error, Result};
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerName};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// TLS settings for the connections to a service.
+///
+/// By default, server certificates are validated against the system's root certificates.
+/// Additional root certificates, e.g. of an institutional CA, can be trusted as well, and
+/// the system roots can be disabled altogether. A client certificate can be presented to
+/// services requiring mutual TLS, and the server certificate can be pinned to a known
+/// SHA-256 fingerprint.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ga4gh_sdk::utils::configuration::Configuration;
+/// use ga4gh_sdk::utils::tls::TlsConfig;
+///
+/// # fn example() -> ga4gh_sdk::Result<()> {
+/// let tls = TlsConfig::default()
+///     .with_root_certificate(std::fs::read("/etc/ssl/institutional-ca.pem")?)
+///     .with_client_identity(std::fs::read("client.pem")?, std::fs::read("client.key")?);
+/// let config = Configuration::new(url::Url::parse("https://tes.example.org").unwrap())
+///     .with_tls(tls);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+    /// Additional trusted root certificates, each a PEM document holding one or more certificates.
+    pub root_certificates: Vec<Vec<u8>>,
+    /// Whether the system's root certificates are trusted.
+    pub use_system_roots: bool,
+    /// The client certificate chain and private key, as PEM, presented for mutual TLS.
+    pub client_identity: Option<ClientIdentity>,
+    /// The SHA-256 fingerprint the server's certificate must have, as hex.
+    pub pinned_sha256: Option<String>,
+}
+
+/// A client certificate chain and its private key, both as PEM.
+#[derive(Clone, PartialEq)]
+pub struct ClientIdentity {
+    /// The PEM-encoded certificate chain, starting with the client certificate.
+    pub certificate_chain: Vec<u8>,
+    /// The PEM-encoded private key (PKCS#8, PKCS#1 or SEC1).
+    pub private_key: Vec<u8>,
+}
+
+impl std::fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientIdentity")
+            .field("certificate_chain", &String::from_utf8_lossy(&self.certificate_chain))
+            .field("private_key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        TlsConfig {
+            root_certificates: Vec::new(),
+            use_system_roots: true,
+            client_identity: None,
+            pinned_sha256: None,
+        }
+    }
+}
+
+impl TlsConfig {
+    /// Adds a trusted root certificate.
+    ///
+    /// # Arguments
+    ///
+    /// * `pem` - A PEM document holding one or more certificates.
+    pub fn with_root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Sets whether the system's root certificates are trusted.
+    pub fn with_system_roots(mut self, use_system_roots: bool) -> Self {
+        self.use_system_roots = use_system_roots;
+        self
+    }
+
+    /// Sets the client certificate and key presented for mutual TLS.
+    ///
+    /// # Arguments
+    ///
+    /// * `certificate_chain` - The PEM-encoded certificate chain, starting with the client certificate.
+    /// * `private_key` - The PEM-encoded private key of the client certificate.
+    pub fn with_client_identity(mut self, certificate_chain: impl Into<Vec<u8>>, private_key: impl Into<Vec<u8>>) -> Self {
+        self.client_identity = Some(ClientIdentity {
+            certificate_chain: certificate_chain.into(),
+            private_key: private_key.into(),
+        });
+        self
+    }
+
+    /// Pins the server certificate to the given SHA-256 fingerprint.
+    ///
+    /// The certificate must still be trusted by one of the root certificates; the pin is
+    /// checked in addition.
+    ///
+    /// # Arguments
+    ///
+    /// * `fingerprint` - The fingerprint as hex, optionally with `:` separators.
+    pub fn with_pinned_sha256(mut self, fingerprint: impl Into<String>) -> Self {
+        self.pinned_sha256 = Some(fingerprint.into());
+        self
+    }
+
+    /// Returns whether these are the default settings, which need no custom TLS setup.
+    pub fn is_default(&self) -> bool {
+        *self == TlsConfig::default()
+    }
+
+    /// Reads the TLS settings from the `tls` object of a service in the configuration file.
+    ///
+    /// Certificates and keys are given as paths to PEM files:
+    ///
+    /// ```json
+    /// {
+    ///     "ca_certs": ["/etc/ssl/institutional-ca.pem"],
+    ///     "use_system_roots": true,
+    ///     "client_cert": "/home/me/.ga4gh-cli/client.pem",
+    ///     "client_key": "/home/me/.ga4gh-cli/client.key",
+    ///     "pinned_sha256": "5f:2b:..."
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Configuration` if a value has the wrong type or a file cannot be read.
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let mut tls = TlsConfig::default();
+        match &json["ca_certs"] {
+            Value::Null => {}
+            Value::Array(paths) => {
+                for path in paths {
+                    let path = path
+                        .as_str()
+                        .ok_or_else(|| Error::Configuration("'tls.ca_certs' must be a list of paths".to_string()))?;
+                    tls = tls.with_root_certificate(read_pem_file(path)?);
+                }
+            }
+            _ => return Err(Error::Configuration("'tls.ca_certs' must be a list of paths".to_string())),
+        }
+        if let Some(use_system_roots) = json.get("use_system_roots") {
+            let use_system_roots = use_system_roots
+                .as_bool()
+                .ok_or_else(|| Error::Configuration("'tls.use_system_roots' must be a boolean".to_string()))?;
+            tls = tls.with_system_roots(use_system_roots);
+        }
+        match (json["client_cert"].as_str(), json["client_key"].as_str()) {
+            (Some(cert), Some(key)) => {
+                tls = tls.with_client_identity(read_pem_file(cert)?, read_pem_file(key)?);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(Error::Configuration(
+                    "'tls.client_cert' and 'tls.client_key' must be given together".to_string(),
+                ))
+            }
+        }
+        if let Some(fingerprint) = json["pinned_sha256"].as_str() {
+            tls = tls.with_pinned_sha256(fingerprint);
+        }
+        Ok(tls)
+    }
+
+    /// Builds the `rustls` client configuration for these settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Configuration` if a certificate, key or fingerprint is invalid.
+    pub(crate) fn client_config(&self) -> Result<ClientConfig> {
+        let mut roots = RootCertStore::empty();
+        if self.use_system_roots {
+            let native_certs = rustls_native_certs::load_native_certs()
+                .map_err(|e| Error::Configuration(format!("Failed to load system root certificates: {}", e)))?;
+            for cert in native_certs {
+                // ignore the odd unparsable system certificate, as browsers do
+                let _ = roots.add(&Certificate(cert.0));
+            }
+        }
+        for pem in &self.root_certificates {
+            for cert in parse_certificates(pem)? {
+                roots
+                    .add(&cert)
+                    .map_err(|e| Error::Configuration(format!("Invalid root certificate: {}", e)))?;
+            }
+        }
+
+        let mut verifier: Arc<dyn ServerCertVerifier> = Arc::new(WebPkiVerifier::new(roots, None));
+        if let Some(ref fingerprint) = self.pinned_sha256 {
+            verifier = Arc::new(PinnedVerifier {
+                inner: verifier,
+                fingerprint: parse_fingerprint(fingerprint)?,
+            });
+        }
+
+        let builder = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(verifier);
+        let config = match self.client_identity {
+            Some(ref identity) => {
+                let chain = parse_certificates(&identity.certificate_chain)?;
+                let key = parse_private_key(&identity.private_key)?;
+                builder
+                    .with_client_auth_cert(chain, key)
+                    .map_err(|e| Error::Configuration(format!("Invalid client certificate or key: {}", e)))?
+            }
+            None => builder.with_no_client_auth(),
+        };
+        Ok(config)
+    }
+}
+
+/// Validates the server certificate with an inner verifier, then checks its fingerprint.
+struct PinnedVerifier {
+    inner: Arc<dyn ServerCertVerifier>,
+    fingerprint: [u8; 32],
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        self.inner
+            .verify_server_cert(end_entity, intermediates, server_name, scts, ocsp_response, now)?;
+        let fingerprint: [u8; 32] = Sha256::digest(&end_entity.0).into();
+        if fingerprint != self.fingerprint {
+            return Err(rustls::Error::General(
+                "server certificate does not match the pinned SHA-256 fingerprint".to_string(),
+            ));
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+fn read_pem_file(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    std::fs::read(path).map_err(|e| Error::Configuration(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+fn parse_certificates(pem: &[u8]) -> Result<Vec<Certificate>> {
+    let certs = rustls_pemfile::certs(&mut &*pem)
+        .map_err(|e| Error::Configuration(format!("Invalid PEM certificate: {}", e)))?;
+    if certs.is_empty() {
+        return Err(Error::Configuration("No certificate found in PEM data".to_string()));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn parse_private_key(mut pem: &[u8]) -> Result<PrivateKey> {
+    loop {
+        match rustls_pemfile::read_one(&mut pem) {
+            Ok(Some(rustls_pemfile::Item::PKCS8Key(key)))
+            | Ok(Some(rustls_pemfile::Item::RSAKey(key)))
+            | Ok(Some(rustls_pemfile::Item::ECKey(key))) => return Ok(PrivateKey(key)),
+            Ok(Some(_)) => continue,
+            Ok(None) => return Err(Error::Configuration("No private key found in PEM data".to_string())),
+            Err(e) => return Err(Error::Configuration(format!("Invalid PEM private key: {}", e))),
+        }
+    }
+}
+
+fn parse_fingerprint(fingerprint: &str) -> Result<[u8; 32]> {
+    let hex: String = fingerprint.chars().filter(|c| *c != ':').collect();
+    let invalid = || Error::Configuration(format!("Invalid SHA-256 fingerprint: {}", fingerprint));
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::configuration::Configuration;
+    use crate::utils::transport::Transport;
+    use rustls::server::AllowAnyAuthenticatedClient;
+    use rustls::ServerConfig;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+    use url::Url;
+
+    struct TestCertificate {
+        pem: String,
+        key_pem: String,
+        der: Vec<u8>,
+    }
+
+    fn self_signed(name: &str) -> TestCertificate {
+        let certified = rcgen::generate_simple_self_signed(vec![name.to_string()]).unwrap();
+        TestCertificate {
+            pem: certified.cert.pem(),
+            key_pem: certified.key_pair.serialize_pem(),
+            der: certified.cert.der().to_vec(),
+        }
+    }
+
+    /// Starts a local HTTPS server answering every request with `ok`, optionally requiring
+    /// a client certificate issued by `client_ca`.
+    async fn https_mock(server: &TestCertificate, client_ca: Option<&TestCertificate>) -> Url {
+        let builder = ServerConfig::builder().with_safe_defaults();
+        let builder = match client_ca {
+            Some(ca) => {
+                let mut roots = RootCertStore::empty();
+                roots.add(&Certificate(ca.der.clone())).unwrap();
+                builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let config = builder
+            .with_single_cert(
+                parse_certificates(server.pem.as_bytes()).unwrap(),
+                parse_private_key(server.key_pem.as_bytes()).unwrap(),
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let Ok(mut stream) = acceptor.accept(stream).await else { return };
+                    let mut buf = vec![0u8; 4096];
+                    let _ = stream.read(&mut buf).await;
+                    let response = "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok";
+                    let _ = stream.write_all(response.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+
+        Url::parse(&format!("https://localhost:{}", port)).unwrap()
+    }
+
+    fn transport(url: Url, tls: TlsConfig) -> Transport {
+        Transport::new(&Configuration::new(url).with_tls(tls)).unwrap()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")
+    }
+
+    #[tokio::test]
+    async fn test_extra_root_certificate() {
+        let server = self_signed("localhost");
+        let url = https_mock(&server, None).await;
+
+        let untrusted = transport(url.clone(), TlsConfig::default()).get("/", None).await;
+        assert!(untrusted.is_err());
+
+        let tls = TlsConfig::default()
+            .with_system_roots(false)
+            .with_root_certificate(server.pem.clone());
+        assert_eq!(transport(url, tls).get("/", None).await.unwrap(), "ok");
+    }
+
+    #[tokio::test]
+    async fn test_pinned_fingerprint() {
+        let server = self_signed("localhost");
+        let url = https_mock(&server, None).await;
+        let trusted = TlsConfig::default().with_root_certificate(server.pem.clone());
+
+        let pinned = trusted.clone().with_pinned_sha256(hex(&Sha256::digest(&server.der)));
+        assert_eq!(transport(url.clone(), pinned).get("/", None).await.unwrap(), "ok");
+
+        let other = self_signed("localhost");
+        let mispinned = trusted.with_pinned_sha256(hex(&Sha256::digest(&other.der)));
+        assert!(transport(url, mispinned).get("/", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate() {
+        let server = self_signed("localhost");
+        let client = self_signed("client");
+        let url = https_mock(&server, Some(&client)).await;
+        let trusted = TlsConfig::default().with_root_certificate(server.pem.clone());
+
+        let anonymous = transport(url.clone(), trusted.clone()).get("/", None).await;
+        assert!(anonymous.is_err());
+
+        let mtls = trusted.with_client_identity(client.pem, client.key_pem);
+        assert_eq!(transport(url, mtls).get("/", None).await.unwrap(), "ok");
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let tls = TlsConfig::default().with_root_certificate("not a certificate");
+        assert!(matches!(tls.client_config(), Err(Error::Configuration(_))));
+
+        let tls = TlsConfig::default().with_pinned_sha256("abc");
+        assert!(matches!(tls.client_config(), Err(Error::Configuration(_))));
+
+        let json = serde_json::json!({ "client_cert": "/tmp/client.pem" });
+        assert!(matches!(TlsConfig::from_json(&json), Err(Error::Configuration(_))));
+    }
+}
@@ -1,20 +1,17 @@
 #[cfg(feature = "integration_tests")]
 #[cfg(test)]
 mod tests {
-    use ga4gh_sdk::utils::configuration::Configuration;
     use ga4gh_sdk::clients::tes::models::TesTask;
     use ga4gh_sdk::clients::tes::models::ListTasksParams;
     use ga4gh_sdk::clients::tes::Task;
     use ga4gh_sdk::clients::tes::models::TesState;
+    use ga4gh_sdk::clients::tes::models::TesView;
     use ga4gh_sdk::clients::tes::TES;
-    use ga4gh_sdk::utils::test_utils::{ensure_funnel_running, setup};
+    use ga4gh_sdk::utils::test_utils::{funnel_configuration, setup};
 
-    async fn create_task() -> Result<(Task, TES), Box<dyn std::error::Error>> {
+    async fn create_task(test_name: &str) -> ga4gh_sdk::Result<(Task, TES)> {
         // setup(); – should be run once in the test function
-        let mut config = Configuration::default();
-        let funnel_url = ensure_funnel_running().await;
-        let funnel_url = url::Url::parse(&funnel_url).expect("Invalid URL");
-        config.set_base_path(funnel_url);
+        let config = funnel_configuration(test_name).await;
         let tes = match TES::new(&config).await {
             Ok(tes) => tes,
             Err(e) => {
@@ -39,7 +36,7 @@ mod tests {
     #[tokio::test]
     async fn test_task_create() {
         setup();
-        let (task, _tes) = create_task().await.expect("Failed to create task");
+        let (task, _tes) = create_task("task_create").await.expect("Failed to create task");
         assert!(!task.id.is_empty(), "Task ID should not be empty"); // double check if it's a correct assertion
     }
 
@@ -47,7 +44,7 @@ mod tests {
     async fn test_task_status() {
         setup();
 
-        let (task, _tes) = create_task().await.expect("Failed to create task");
+        let (task, _tes) = create_task("task_status").await.expect("Failed to create task");
         assert!(!task.id.is_empty(), "Task ID should not be empty");
 
         let status = task.status().await;
@@ -69,7 +66,7 @@ mod tests {
     async fn test_cancel_task() {
         setup();
 
-        let (task, _tes) = &create_task().await.expect("Failed to create task");
+        let (task, _tes) = &create_task("cancel_task").await.expect("Failed to create task");
         assert!(!task.id.is_empty(), "Task ID should not be empty"); // double check if it's a correct assertion
 
         let cancel = task.cancel().await;
@@ -80,7 +77,7 @@ mod tests {
     async fn test_list_task() {
         setup();
 
-        let (task, tes) = &create_task().await.expect("Failed to create task");
+        let (task, tes) = &create_task("list_task").await.expect("Failed to create task");
         assert!(!task.id.is_empty(), "Task ID should not be empty"); // double check if it's a correct assertion
 
         let params: ListTasksParams = ListTasksParams {
@@ -90,7 +87,7 @@ mod tests {
             tag_value: None,
             page_size: None,
             page_token: None,
-            view: Some("BASIC".to_string()),
+            view: Some(TesView::Basic),
         };
 
         let list = tes.list_tasks(Some(params)).await;
@@ -2,6 +2,10 @@ use pyo3::prelude::*;
 use ga4gh_sdk::clients::tes::models::TesTask;
 use ga4gh_sdk::clients::tes::models::ListTasksParams;
 use ga4gh_sdk::clients::tes::models::TesListTasksResponse;
+use ga4gh_sdk::clients::tes::models::TesView;
+use ga4gh_sdk::clients::tes::template::TemplateVars;
+use std::collections::HashMap;
+use std::path::Path;
 use ga4gh_sdk::clients::tes::TES;
 use ga4gh_sdk::clients::tes::Task;
 use crate::PyConfiguration;
@@ -108,7 +112,16 @@ impl PyTES {
         }
     }
 
-    pub fn create(&self, task_file_path: String) -> PyResult<PyTask> {
+    /// Creates a task from a task file, substituting its `${VAR}` and `${VAR:-default}`
+    /// placeholders with `vars`, the variables of `vars_file` and the environment.
+    #[pyo3(signature = (task_file_path, vars=None, vars_file=None, strict=true))]
+    pub fn create(
+        &self,
+        task_file_path: String,
+        vars: Option<HashMap<String, String>>,
+        vars_file: Option<String>,
+        strict: bool,
+    ) -> PyResult<PyTask> {
         let rt = Runtime::new().unwrap();
 
         let task_json = match std::fs::read_to_string(task_file_path.clone()) {
@@ -121,6 +134,19 @@ impl PyTES {
         let task: TesTask = serde_json::from_str(&task_json)
             .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Failed to parse JSON: {}", e)))?;
 
+        let mut template_vars = TemplateVars::new().with_strict(strict);
+        if let Some(vars_file) = vars_file {
+            template_vars = template_vars
+                .with_file(Path::new(&vars_file))
+                .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("{}", e)))?;
+        }
+        for (key, value) in vars.unwrap_or_default() {
+            template_vars = template_vars.with_var(key, value);
+        }
+        let task = template_vars
+            .apply(&task)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("{}", e)))?;
+
         match rt.block_on(self.inner.create(task.clone())) {
             Ok(task) => Ok(PyTask { inner: task }),
             Err(e) => Err(pyo3::exceptions::PyRuntimeError::new_err(format!(
@@ -131,6 +157,9 @@ impl PyTES {
     }
 
     pub fn get(&self, view: &str, id: &str) -> PyResult<PyTesTask> {
+        let view: TesView = view
+            .parse()
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("{}", e)))?;
         let rt = Runtime::new().unwrap();
         match rt.block_on(self.inner.get(view, id)) {
             Ok(task) => Ok(PyTesTask { inner: task }),
@@ -27,7 +27,7 @@ API_NAME="$2"
 DESTINATION_DIR="$3"
 
 # Define constants
-SED_RULE="s/^use\s\+crate::models\s*;/#![allow(unused_imports)]\n#![allow(clippy::empty_docs)]\nuse crate::clients::$API_NAME::models;/"
+SED_RULE="s/^use\s\+crate::models\s*;/#![allow(unused_imports)]\n#![allow(clippy::empty_docs)]\n#![allow(clippy::derivable_impls)]\n#![allow(clippy::too_many_arguments)]\nuse crate::clients::$API_NAME::models;/"
 TEMP_OUTPUT_DIR=$(mktemp -d)  # Define the temporary output directory for the OpenAPI generator
 
 # Exit immediately if a command exits with a non-zero status.